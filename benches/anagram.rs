@@ -0,0 +1,56 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use rust_exercism::anagram::{self, AnagramOptions};
+
+/// Words of 3 to 10 `letters` from a fixed LCG, so runs compare. A small
+/// alphabet makes a fair share of them anagrams of each other.
+fn words(count: usize, letters: &[char]) -> Vec<String> {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut next = move || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (state >> 33) as usize
+    };
+
+    (0..count)
+        .map(|_| {
+            let len = 3 + next() % 8;
+            (0..len).map(|_| letters[next() % letters.len()]).collect()
+        })
+        .collect()
+}
+
+fn bench_anagram(c: &mut Criterion) {
+    let inputs = [
+        (
+            "ascii",
+            "salient",
+            words(200_000, &['a', 'e', 'i', 'l', 'n', 'r', 's', 't']),
+        ),
+        (
+            "unicode",
+            "αβγδεζ",
+            words(200_000, &['α', 'β', 'Γ', 'δ', 'ε', 'ζ', 'é', 'ß']),
+        ),
+    ];
+
+    for (name, word, candidates) in &inputs {
+        let candidates: Vec<&str> = candidates.iter().map(String::as_str).collect();
+        let mut group = c.benchmark_group(format!("anagrams_for/{name}"));
+        group.throughput(Throughput::Elements(candidates.len() as u64));
+
+        group.bench_function("sorted_graphemes", |b| {
+            b.iter(|| {
+                anagram::anagrams_for_with(word, black_box(&candidates), &AnagramOptions::default())
+            })
+        });
+        group.bench_function("counted", |b| {
+            b.iter(|| anagram::anagrams_for(word, black_box(&candidates)))
+        });
+        group.bench_function("parallel", |b| {
+            b.iter(|| anagram::anagrams_for_parallel(word, black_box(&candidates), 4))
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_anagram);
+criterion_main!(benches);
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use rust_exercism::parallel_letter_frequency::{frequency, FrequencyCounter};
+
+const LINE: &str = "O say can you see by the dawn's early light, Freude schöner Götterfunken";
+
+/// Inputs of about `bytes` each, shaped to trip up splitting by line count.
+fn skewed(bytes: usize) -> Vec<(&'static str, Vec<String>)> {
+    let lines = bytes / LINE.len();
+    let huge = LINE.repeat(lines);
+    let tiny = vec!["a".to_string(); bytes];
+    let mut mixed = vec!["b".to_string(); bytes / 2];
+    mixed.push(LINE.repeat(lines / 2));
+
+    vec![
+        ("one_huge_line", vec![huge]),
+        ("many_tiny_lines", tiny),
+        ("tiny_lines_then_huge", mixed),
+        ("even_lines", vec![LINE.to_string(); lines]),
+    ]
+}
+
+fn bench_frequency(c: &mut Criterion) {
+    let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+    for (name, lines) in skewed(4 << 20) {
+        let input: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let bytes: usize = input.iter().map(|line| line.len()).sum();
+        let mut group = c.benchmark_group(format!("frequency/{name}"));
+        group.throughput(Throughput::Bytes(bytes as u64));
+        group.sample_size(10);
+
+        group.bench_function("1_worker", |b| b.iter(|| frequency(black_box(&input), 1)));
+        group.bench_function("all_cores", |b| {
+            b.iter(|| frequency(black_box(&input), workers))
+        });
+        let mut counter = FrequencyCounter::new(workers);
+        group.bench_function("counter", |b| b.iter(|| counter.count(black_box(&input))));
+        group.finish();
+    }
+}
+
+const ANTHEMS: [&str; 24] = [
+    "Freude schöner Götterfunken",
+    "Tochter aus Elysium,",
+    "Wir betreten feuertrunken,",
+    "Himmlische, dein Heiligtum!",
+    "Deine Zauber binden wieder",
+    "Was die Mode streng geteilt;",
+    "Alle Menschen werden Brüder,",
+    "Wo dein sanfter Flügel weilt.",
+    "Wilhelmus van Nassouwe",
+    "ben ik, van Duitsen bloed,",
+    "den vaderland getrouwe",
+    "blijf ik tot in den dood.",
+    "Een Prinse van Oranje",
+    "ben ik, vrij, onverveerd,",
+    "den Koning van Hispanje",
+    "heb ik altijd geëerd.",
+    "O say can you see by the dawn's early light,",
+    "What so proudly we hailed at the twilight's last gleaming,",
+    "Whose broad stripes and bright stars through the perilous fight,",
+    "O'er the ramparts we watched, were so gallantly streaming?",
+    "And the rockets' red glare, the bombs bursting in air,",
+    "Gave proof through the night that our flag was still there;",
+    "O say does that star-spangled banner yet wave,",
+    "O'er the land of the free and the home of the brave?",
+];
+
+/// Size of the anthem corpus: `FREQUENCY_BENCH_BYTES`, with an optional
+/// K, M or G suffix, or 64M. Set it to 1G or more to measure at scale.
+fn corpus_bytes() -> usize {
+    let Ok(value) = std::env::var("FREQUENCY_BENCH_BYTES") else {
+        return 64 << 20;
+    };
+    let (digits, shift) = match value.trim().to_ascii_uppercase() {
+        v if v.ends_with('G') => (v[..v.len() - 1].to_string(), 30),
+        v if v.ends_with('M') => (v[..v.len() - 1].to_string(), 20),
+        v if v.ends_with('K') => (v[..v.len() - 1].to_string(), 10),
+        v => (v, 0),
+    };
+    let count: usize = digits
+        .parse()
+        .unwrap_or_else(|_| panic!("invalid FREQUENCY_BENCH_BYTES: {value}"));
+    count << shift
+}
+
+/// The per-character map counting `frequency` did before the ASCII fast
+/// path, as a baseline.
+fn char_map(input: &[&str]) -> HashMap<char, usize> {
+    let mut map = HashMap::new();
+    for c in input.iter().flat_map(|line| line.chars()) {
+        if c.is_alphabetic() {
+            for lower in c.to_lowercase() {
+                *map.entry(lower).or_default() += 1;
+            }
+        }
+    }
+    map
+}
+
+fn bench_anthems(c: &mut Criterion) {
+    let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let verse = ANTHEMS.join("\n") + "\n";
+    let text = verse.repeat(corpus_bytes().div_ceil(verse.len()));
+    let input: Vec<&str> = text.lines().collect();
+
+    let mut group = c.benchmark_group("frequency/anthems");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.sample_size(10);
+
+    group.bench_function("char_map", |b| b.iter(|| char_map(black_box(&input))));
+    group.bench_function("1_worker", |b| b.iter(|| frequency(black_box(&input), 1)));
+    group.bench_function("all_cores", |b| {
+        b.iter(|| frequency(black_box(&input), workers))
+    });
+    group.finish();
+}
+
+/// What the std counter's copy of each share into a worker's buffer costs,
+/// next to counting the same text and to spawning threads per call.
+fn bench_counter_copy(c: &mut Criterion) {
+    let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let lines = vec![LINE.to_string(); (1 << 20) / LINE.len()];
+    let input: Vec<&str> = lines.iter().map(String::as_str).collect();
+    let bytes: usize = input.iter().map(|line| line.len() + 1).sum();
+
+    let mut group = c.benchmark_group("frequency/counter_copy");
+    group.throughput(Throughput::Bytes(bytes as u64));
+
+    let mut buffer = String::with_capacity(bytes);
+    group.bench_function("copy", |b| {
+        b.iter(|| {
+            buffer.clear();
+            for line in black_box(&input) {
+                buffer.push_str(line);
+                buffer.push('\n');
+            }
+            buffer.len()
+        })
+    });
+    group.bench_function("1_worker", |b| b.iter(|| frequency(black_box(&input), 1)));
+    group.bench_function("all_cores", |b| {
+        b.iter(|| frequency(black_box(&input), workers))
+    });
+    let mut counter = FrequencyCounter::new(workers);
+    group.bench_function("counter", |b| b.iter(|| counter.count(black_box(&input))));
+    group.finish();
+}
+
+criterion_group!(benches, bench_frequency, bench_anthems, bench_counter_copy);
+criterion_main!(benches);
@@ -0,0 +1,76 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use rust_exercism::luhn;
+
+/// Newline-delimited 16-digit numbers from a fixed LCG, so runs compare.
+fn numbers(count: usize) -> String {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut buffer = String::with_capacity(count * 20);
+
+    for _ in 0..count {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+        let digits = format!("{:016}", state % 10_000_000_000_000_000);
+        buffer.push_str(&luhn::format(&digits, &[4]));
+        buffer.push('\n');
+    }
+    buffer
+}
+
+fn bench_luhn(c: &mut Criterion) {
+    let buffer = numbers(100_000);
+    let mut group = c.benchmark_group("luhn");
+    group.throughput(Throughput::Bytes(buffer.len() as u64));
+
+    group.bench_function("is_valid", |b| {
+        b.iter(|| {
+            black_box(&buffer)
+                .lines()
+                .filter(|l| luhn::is_valid(l))
+                .count()
+        })
+    });
+    group.bench_function("is_valid_bytes", |b| {
+        b.iter(|| {
+            black_box(&buffer)
+                .as_bytes()
+                .split(|&b| b == b'\n')
+                .filter(|l| luhn::is_valid_bytes(l))
+                .count()
+        })
+    });
+    group.bench_function("validate_lines", |b| {
+        b.iter(|| {
+            luhn::validate_lines(black_box(buffer.as_bytes()))
+                .filter(|&v| v)
+                .count()
+        })
+    });
+    group.finish();
+
+    let values: Vec<u64> = buffer
+        .lines()
+        .map(|l| l.replace(' ', "").parse().unwrap())
+        .collect();
+    let mut group = c.benchmark_group("luhn_u64");
+    group.throughput(Throughput::Elements(values.len() as u64));
+
+    group.bench_function("is_valid", |b| {
+        b.iter(|| {
+            black_box(&values)
+                .iter()
+                .filter(|v| luhn::is_valid(&v.to_string()))
+                .count()
+        })
+    });
+    group.bench_function("is_valid_u64", |b| {
+        b.iter(|| {
+            black_box(&values)
+                .iter()
+                .filter(|&&v| luhn::is_valid_u64(v))
+                .count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_luhn);
+criterion_main!(benches);
@@ -17,6 +17,28 @@
 use std::collections::HashSet;
 use unicode_segmentation::UnicodeSegmentation;
 
+mod external;
+mod index;
+mod matches;
+mod near;
+mod options;
+mod phrase;
+mod predicates;
+mod rack;
+mod signature;
+mod stored;
+
+pub use external::{group_anagrams, AnagramGroups, GroupOptions};
+pub use index::AnagramIndex;
+pub use matches::{anagram_matches, AnagramMatches};
+pub use near::{anagram_distance, near_anagrams_for, NearAnagram};
+pub use options::{anagrams_for_with, turkish, AnagramOptions, CaseMapping, Normalization};
+pub use phrase::{phrase_anagrams_for, solve_phrase, Ignore, PhraseLimits};
+pub use predicates::{can_form_palindrome, is_sub_anagram, letters_to_add};
+pub use rack::{scrabble_score, sub_anagrams, RackMatch, RackQuery, BLANK};
+pub use signature::anagrams_for_parallel;
+pub use stored::{StoreError, StoredIndex, FORMAT_VERSION};
+
 /// Convert a word into a sorted list of unicode graphemes
 fn convert_to_anagram(word: &str) -> Vec<String> {
     let mut result: Vec<String> = word.graphemes(true).map(|c| c.to_uppercase()).collect();
@@ -24,16 +46,47 @@ fn convert_to_anagram(word: &str) -> Vec<String> {
     result
 }
 
+/// Remove the graphemes of `needle` from `haystack`, both sorted as
+/// [`convert_to_anagram`] leaves them, or `None` if some are missing.
+fn subtract_anagram(haystack: &[String], needle: &[String]) -> Option<Vec<String>> {
+    let mut rest = Vec::with_capacity(haystack.len().saturating_sub(needle.len()));
+    let mut needle = needle.iter().peekable();
+
+    for grapheme in haystack {
+        if needle.peek() == Some(&grapheme) {
+            needle.next();
+        } else if needle.peek().is_some_and(|&n| n < grapheme) {
+            return None;
+        } else {
+            rest.push(grapheme.clone());
+        }
+    }
+    needle.peek().is_none().then_some(rest)
+}
+
+/// Split two sorted signatures into the graphemes only `a` has and the ones
+/// only `b` has, counting repeats.
+fn diff_anagrams(a: &[String], b: &[String]) -> (Vec<String>, Vec<String>) {
+    let (mut only_a, mut only_b) = (Vec::new(), Vec::new());
+    let (mut a, mut b) = (a.iter().peekable(), b.iter().peekable());
+
+    loop {
+        match (a.peek(), b.peek()) {
+            (Some(x), Some(y)) if x == y => {
+                a.next();
+                b.next();
+            }
+            (Some(x), Some(y)) if x < y => only_a.extend(a.next().cloned()),
+            (Some(_), Some(_)) | (None, Some(_)) => only_b.extend(b.next().cloned()),
+            (Some(_), None) => only_a.extend(a.next().cloned()),
+            (None, None) => return (only_a, only_b),
+        }
+    }
+}
+
 pub fn anagrams_for<'a>(word: &str, possible_anagrams: &[&'a str]) -> HashSet<&'a str> {
-    let word_anagram = convert_to_anagram(word);
-
-    possible_anagrams
-        .iter()
-        .cloned()
-        .filter(|anagram| {
-            convert_to_anagram(anagram) == word_anagram
-                && word.to_uppercase() != anagram.to_uppercase()
-        })
+    anagram_matches(word, possible_anagrams.iter().cloned())
+        .map(|(_, anagram)| anagram)
         .collect()
 }
 
@@ -0,0 +1,376 @@
+//! Anagram families of word lists too big for memory, grouped with an
+//! external merge sort.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, Write};
+use std::iter::FusedIterator;
+use std::path::PathBuf;
+use std::vec;
+
+use super::AnagramOptions;
+
+/// A word with its signature, ordered by signature and then word.
+type Record = (Vec<String>, String);
+
+/// Settings for [`group_anagrams`].
+#[derive(Debug, Clone)]
+pub struct GroupOptions {
+    /// Roughly how many bytes of words and signatures to sort in memory
+    /// before spilling them to a temporary file.
+    pub run_bytes: usize,
+    /// Most runs merged at once. Spilled runs are merged this many at a
+    /// time as they pile up, so the temporary files held open grow with the
+    /// logarithm of the input rather than with its size. Values below 2 are
+    /// treated as 2.
+    pub fan_in: usize,
+    /// Where to put the temporary files, or the system default.
+    pub temp_dir: Option<PathBuf>,
+    /// How words are compared.
+    pub anagram: AnagramOptions,
+}
+
+impl Default for GroupOptions {
+    fn default() -> Self {
+        Self {
+            run_bytes: 64 << 20,
+            fan_in: 64,
+            temp_dir: None,
+            anagram: AnagramOptions::default(),
+        }
+    }
+}
+
+/// A sorted run of records, either spilled to disk or the last one, kept in
+/// memory.
+enum Run {
+    Memory(vec::IntoIter<Record>),
+    File(BufReader<File>),
+}
+
+impl Run {
+    /// Write the records `write` produces to a new temporary file.
+    fn spill(
+        options: &GroupOptions,
+        write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+    ) -> io::Result<Self> {
+        let file = match &options.temp_dir {
+            Some(dir) => tempfile::tempfile_in(dir)?,
+            None => tempfile::tempfile()?,
+        };
+        let mut out = BufWriter::new(file);
+        write(&mut out)?;
+        let mut file = out.into_inner().map_err(io::IntoInnerError::into_error)?;
+        file.rewind()?;
+        Ok(Self::File(BufReader::new(file)))
+    }
+
+    fn next_record(&mut self) -> io::Result<Option<Record>> {
+        let input = match self {
+            Self::Memory(records) => return Ok(records.next()),
+            Self::File(input) => input,
+        };
+        if input.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+        let count = read_len(input)?;
+        let signature = (0..count)
+            .map(|_| read_str(input))
+            .collect::<io::Result<_>>()?;
+        Ok(Some((signature, read_str(input)?)))
+    }
+}
+
+fn write_record(out: &mut impl Write, (signature, word): &Record) -> io::Result<()> {
+    write_len(out, signature.len())?;
+    for grapheme in signature {
+        write_str(out, grapheme)?;
+    }
+    write_str(out, word)
+}
+
+fn write_len(out: &mut impl Write, len: usize) -> io::Result<()> {
+    out.write_all(&(len as u64).to_le_bytes())
+}
+
+fn read_len(input: &mut impl Read) -> io::Result<usize> {
+    let mut len = [0; 8];
+    input.read_exact(&mut len)?;
+    Ok(u64::from_le_bytes(len) as usize)
+}
+
+/// Write `text` with its length in front, since words and graphemes can hold
+/// any character.
+fn write_str(out: &mut impl Write, text: &str) -> io::Result<()> {
+    write_len(out, text.len())?;
+    out.write_all(text.as_bytes())
+}
+
+fn read_str(input: &mut impl Read) -> io::Result<String> {
+    let mut text = vec![0; read_len(input)?];
+    input.read_exact(&mut text)?;
+    String::from_utf8(text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// A k-way merge of sorted runs.
+struct Merge {
+    runs: Vec<Run>,
+    heap: BinaryHeap<Reverse<(Record, usize)>>,
+}
+
+impl Merge {
+    fn new(mut runs: Vec<Run>) -> io::Result<Self> {
+        let mut heap = BinaryHeap::with_capacity(runs.len());
+        for (i, run) in runs.iter_mut().enumerate() {
+            if let Some(record) = run.next_record()? {
+                heap.push(Reverse((record, i)));
+            }
+        }
+        Ok(Self { runs, heap })
+    }
+
+    fn next_record(&mut self) -> io::Result<Option<Record>> {
+        let Some(Reverse((record, i))) = self.heap.pop() else {
+            return Ok(None);
+        };
+        if let Some(next) = self.runs[i].next_record()? {
+            self.heap.push(Reverse((next, i)));
+        }
+        Ok(Some(record))
+    }
+
+    /// Merge `runs` into a single spilled run.
+    fn spill(runs: Vec<Run>, options: &GroupOptions) -> io::Result<Run> {
+        let mut merge = Self::new(runs)?;
+        Run::spill(options, |out| {
+            while let Some(record) = merge.next_record()? {
+                write_record(out, &record)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Read one word per line from `input`, ignoring surrounding whitespace and
+/// blank lines, and group them by anagram signature.
+///
+/// At most about [`GroupOptions::run_bytes`] of words are held in memory
+/// while reading. Each full run is sorted and spilled to a temporary file,
+/// deleted once the groups are dropped, and the runs are merged lazily.
+/// Whenever [`GroupOptions::fan_in`] runs of the same size pile up they are
+/// merged into one, and the final merge reads at most that many runs.
+pub fn group_anagrams(input: impl BufRead, options: &GroupOptions) -> io::Result<AnagramGroups> {
+    let fan_in = options.fan_in.max(2);
+    // levels[n] holds runs made of fan_in^n spills each
+    let mut levels: Vec<Vec<Run>> = Vec::new();
+    let mut records: Vec<Record> = Vec::new();
+    let mut bytes = 0;
+
+    for line in input.lines() {
+        let line = line?;
+        let word = line.trim();
+        if word.is_empty() {
+            continue;
+        }
+        let signature = options.anagram.signature(word);
+        bytes += word.len() + signature.iter().map(|g| g.len() + 24).sum::<usize>() + 48;
+        records.push((signature, word.to_string()));
+
+        if bytes >= options.run_bytes {
+            records.sort_unstable();
+            let mut run = Run::spill(options, |out| {
+                records
+                    .iter()
+                    .try_for_each(|record| write_record(out, record))
+            })?;
+            records.clear();
+            bytes = 0;
+
+            for level in 0.. {
+                if level == levels.len() {
+                    levels.push(Vec::new());
+                }
+                levels[level].push(run);
+                if levels[level].len() < fan_in {
+                    break;
+                }
+                run = Merge::spill(std::mem::take(&mut levels[level]), options)?;
+            }
+        }
+    }
+
+    // the runs left over, smallest first, plus the one still in memory
+    let mut runs: Vec<Run> = levels.into_iter().flatten().collect();
+    while runs.len() >= fan_in {
+        let rest = runs.split_off(fan_in);
+        runs = std::iter::once(Merge::spill(runs, options)?)
+            .chain(rest)
+            .collect();
+    }
+    records.sort_unstable();
+    runs.push(Run::Memory(records.into_iter()));
+
+    Ok(AnagramGroups {
+        merge: Merge::new(runs)?,
+        group: None,
+    })
+}
+
+/// Iterator over the families of two or more distinct words sharing a
+/// signature, in signature order, each sorted. Created by
+/// [`group_anagrams`].
+pub struct AnagramGroups {
+    merge: Merge,
+    /// The signature and words of the family being gathered.
+    group: Option<(Vec<String>, Vec<String>)>,
+}
+
+impl AnagramGroups {
+    fn next_group(&mut self) -> io::Result<Option<Vec<String>>> {
+        while let Some((signature, word)) = self.merge.next_record()? {
+            match &mut self.group {
+                Some((current, family)) if *current == signature => {
+                    if family.last() != Some(&word) {
+                        family.push(word);
+                    }
+                }
+                group => {
+                    let previous = group.replace((signature, vec![word]));
+                    if let Some((_, family)) = previous.filter(|(_, family)| family.len() > 1) {
+                        return Ok(Some(family));
+                    }
+                }
+            }
+        }
+        Ok(self
+            .group
+            .take()
+            .map(|(_, family)| family)
+            .filter(|family| family.len() > 1))
+    }
+}
+
+impl Iterator for AnagramGroups {
+    type Item = io::Result<Vec<String>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_group() {
+            Ok(group) => group.map(Ok),
+            Err(err) => {
+                // stop after the first error rather than skip records
+                self.merge.heap.clear();
+                self.group = None;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl FusedIterator for AnagramGroups {}
+
+#[cfg(test)]
+mod test {
+    use super::super::{anagrams_for, AnagramIndex};
+    use super::*;
+
+    const WORDS: &str = "enlists\ngoogle\n inlets \nbanana\nListen\n\nsilent\ntinsel\ngallery\nregally\nlargely\nΒΓΑ\nγβα\nsilent\n";
+
+    fn groups(input: &str, options: &GroupOptions) -> Vec<Vec<String>> {
+        group_anagrams(input.as_bytes(), options)
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap()
+    }
+
+    fn sorted(mut words: Vec<String>) -> Vec<String> {
+        words.sort_unstable();
+        words
+    }
+
+    #[test]
+    fn test_groups_match_index() {
+        let index = AnagramIndex::new(WORDS.lines().map(str::trim).filter(|w| !w.is_empty()));
+        let expected: Vec<Vec<String>> = index.groups().map(|g| sorted(g.to_vec())).collect();
+
+        assert_eq!(groups(WORDS, &GroupOptions::default()), expected);
+    }
+
+    #[test]
+    fn test_spilled_runs_give_the_same_groups() {
+        let in_memory = groups(WORDS, &GroupOptions::default());
+        for run_bytes in [1, 100, 300] {
+            let options = GroupOptions {
+                run_bytes,
+                ..Default::default()
+            };
+            assert_eq!(groups(WORDS, &options), in_memory, "{run_bytes}");
+        }
+    }
+
+    #[test]
+    fn test_fan_in_limits_runs_merged_at_once() {
+        let in_memory = groups(WORDS, &GroupOptions::default());
+        for fan_in in [0, 2, 3, 5] {
+            let options = GroupOptions {
+                run_bytes: 1,
+                fan_in,
+                ..Default::default()
+            };
+            let merged = group_anagrams(WORDS.as_bytes(), &options).unwrap();
+            assert!(merged.merge.runs.len() <= fan_in.max(2), "{fan_in}");
+            assert_eq!(
+                merged.collect::<io::Result<Vec<_>>>().unwrap(),
+                in_memory,
+                "{fan_in}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_group_members_are_anagrams() {
+        let options = GroupOptions {
+            run_bytes: 64,
+            ..Default::default()
+        };
+        for group in groups(WORDS, &options) {
+            let members: Vec<&str> = group.iter().map(String::as_str).collect();
+            for word in &members {
+                let matches = anagrams_for(word, &members);
+                assert!(
+                    members.iter().all(|other| matches.contains(other)
+                        || other.to_uppercase() == word.to_uppercase()),
+                    "{group:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_options_are_used() {
+        let options = GroupOptions {
+            run_bytes: 1,
+            anagram: AnagramOptions {
+                ignore_diacritics: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            groups("mesure\nrésumé\ncafé\n", &options),
+            vec![vec!["mesure".to_string(), "résumé".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_empty_input() {
+        assert!(groups("", &GroupOptions::default()).is_empty());
+        assert!(groups("\n  \n", &GroupOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_utf8_is_an_error() {
+        let groups = group_anagrams(&b"ab\n\xff\n"[..], &GroupOptions::default());
+        assert!(groups.is_err());
+    }
+}
@@ -0,0 +1,152 @@
+//! Anagram lookups against a fixed dictionary.
+
+use std::collections::{BTreeMap, HashSet};
+
+use super::AnagramOptions;
+
+/// A dictionary grouped by anagram signature, built once and queried many
+/// times without re-sorting every candidate.
+#[derive(Debug, Clone, Default)]
+pub struct AnagramIndex {
+    families: BTreeMap<Vec<String>, Vec<String>>,
+    options: AnagramOptions,
+}
+
+impl AnagramIndex {
+    pub fn new<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self::with_options(words, AnagramOptions::default())
+    }
+
+    /// Build an index whose words are compared as `options` describes.
+    pub fn with_options<I, S>(words: I, options: AnagramOptions) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut families: BTreeMap<_, Vec<String>> = BTreeMap::new();
+
+        for word in words {
+            let word = word.as_ref();
+            let family = families.entry(options.signature(word)).or_default();
+            if !family.iter().any(|w| w == word) {
+                family.push(word.to_string());
+            }
+        }
+        Self { families, options }
+    }
+
+    /// The options words in the index were compared with.
+    pub fn options(&self) -> &AnagramOptions {
+        &self.options
+    }
+
+    /// The dictionary words that are anagrams of `word`, with the same
+    /// semantics as [`anagrams_for_with`](super::anagrams_for_with).
+    pub fn lookup(&self, word: &str) -> HashSet<&str> {
+        self.families
+            .get(&self.options.signature(word))
+            .into_iter()
+            .flatten()
+            .filter(|candidate| !self.options.same_word(word, candidate))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Every family of two or more words sharing a signature, in signature
+    /// order, each in dictionary order.
+    pub fn groups(&self) -> impl Iterator<Item = &[String]> {
+        self.families
+            .values()
+            .filter(|family| family.len() > 1)
+            .map(Vec::as_slice)
+    }
+
+    /// Number of distinct words in the index.
+    pub fn len(&self) -> usize {
+        self.families.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.families.is_empty()
+    }
+
+    /// Every signature in the index with its words, in signature order.
+    pub(super) fn families(&self) -> impl Iterator<Item = (&[String], &[String])> {
+        self.families
+            .iter()
+            .map(|(signature, words)| (signature.as_slice(), words.as_slice()))
+    }
+}
+
+impl<S: AsRef<str>> FromIterator<S> for AnagramIndex {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        Self::new(iter)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::anagrams_for;
+    use super::*;
+
+    const DICTIONARY: [&str; 12] = [
+        "enlists", "google", "inlets", "banana", "Listen", "silent", "tinsel", "gallery",
+        "regally", "largely", "ΒΓΑ", "γβα",
+    ];
+
+    #[test]
+    fn test_lookup_matches_anagrams_for() {
+        let index = AnagramIndex::new(DICTIONARY);
+
+        for word in ["listen", "LISTEN", "allergy", "banana", "ΑΒΓ", "diaper", ""] {
+            assert_eq!(
+                index.lookup(word),
+                anagrams_for(word, &DICTIONARY),
+                "{word}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_lookup_excludes_the_word_itself() {
+        let index: AnagramIndex = ["banana", "bAnana", "ananab"].into_iter().collect();
+        assert_eq!(index.lookup("banana"), HashSet::from(["ananab"]));
+    }
+
+    #[test]
+    fn test_groups_in_signature_order() {
+        let index = AnagramIndex::new(DICTIONARY);
+        let groups: Vec<&[String]> = index.groups().collect();
+
+        assert_eq!(
+            groups,
+            vec![
+                &["gallery", "regally", "largely"].map(String::from)[..],
+                &["inlets", "Listen", "silent", "tinsel"].map(String::from)[..],
+                &["ΒΓΑ", "γβα"].map(String::from)[..],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lookup_with_options() {
+        let options = AnagramOptions {
+            ignore_diacritics: true,
+            ..Default::default()
+        };
+        let index = AnagramIndex::with_options(["mesure", "Mésuré", "café"], options);
+        assert_eq!(index.lookup("résumé"), HashSet::from(["mesure", "Mésuré"]));
+        assert_eq!(index.lookup("face"), HashSet::from(["café"]));
+    }
+
+    #[test]
+    fn test_duplicate_words_are_stored_once() {
+        let index = AnagramIndex::new(["stop", "pots", "stop"]);
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.groups().count(), 1);
+    }
+}
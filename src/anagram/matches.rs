@@ -0,0 +1,100 @@
+//! Anagram matches from any sequence of candidates, in input order.
+
+use std::iter::{Enumerate, FusedIterator};
+
+use super::signature::{same_word, Signature};
+
+/// Iterator over the candidates that are anagrams of a word, with their
+/// positions in the input. Created by [`anagram_matches`].
+#[derive(Debug, Clone)]
+pub struct AnagramMatches<'w, I> {
+    word: &'w str,
+    signature: Signature,
+    candidates: Enumerate<I>,
+}
+
+/// The `candidates` that are anagrams of `word`, with the same semantics as
+/// [`anagrams_for`](super::anagrams_for), as `(index, candidate)` pairs in
+/// input order. Duplicates are yielded every time they occur.
+///
+/// Candidates can be anything that derefs to text: `&str`, `String`, or
+/// lines read lazily from a file.
+pub fn anagram_matches<I>(word: &str, candidates: I) -> AnagramMatches<'_, I::IntoIter>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    AnagramMatches {
+        word,
+        signature: Signature::new(word),
+        candidates: candidates.into_iter().enumerate(),
+    }
+}
+
+impl<I> Iterator for AnagramMatches<'_, I>
+where
+    I: Iterator,
+    I::Item: AsRef<str>,
+{
+    type Item = (usize, I::Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (word, signature) = (self.word, &self.signature);
+        self.candidates.find(|(_, candidate)| {
+            let candidate = candidate.as_ref();
+            signature.matches(candidate) && !same_word(word, candidate)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.candidates.size_hint().1)
+    }
+}
+
+impl<I> FusedIterator for AnagramMatches<'_, I>
+where
+    I: FusedIterator,
+    I::Item: AsRef<str>,
+{
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_matches_in_input_order_with_indices() {
+        let candidates = ["enlists", "tinsel", "google", "inlets", "Listen", "silent"];
+        let matches: Vec<_> = anagram_matches("listen", candidates).collect();
+        assert_eq!(matches, vec![(1, "tinsel"), (3, "inlets"), (5, "silent")]);
+    }
+
+    #[test]
+    fn test_duplicates_are_kept() {
+        let candidates = ["tan", "nat", "tan"];
+        let matches: Vec<_> = anagram_matches("ant", candidates).collect();
+        assert_eq!(matches, vec![(0, "tan"), (1, "nat"), (2, "tan")]);
+    }
+
+    #[test]
+    fn test_owned_candidates() {
+        let candidates: Vec<String> = vec!["stop".into(), "pots".into(), "spot".into()];
+        let matches: Vec<(usize, String)> = anagram_matches("tops", candidates).collect();
+        assert_eq!(
+            matches,
+            vec![
+                (0, "stop".to_string()),
+                (1, "pots".to_string()),
+                (2, "spot".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lazy_candidates() {
+        let text = "tops\nstop\nopts\nstops\n";
+        let mut matches = anagram_matches("POTS", text.lines());
+        assert_eq!(matches.next(), Some((0, "tops")));
+        assert_eq!(matches.map(|(i, _)| i).collect::<Vec<_>>(), vec![1, 2]);
+    }
+}
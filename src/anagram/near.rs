@@ -0,0 +1,137 @@
+//! Candidates that are anagrams except for a few letters.
+
+use super::signature::same_word;
+use super::{convert_to_anagram, diff_anagrams};
+
+/// A candidate within some distance of being an anagram.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NearAnagram<'a> {
+    pub word: &'a str,
+    /// Graphemes added, removed or substituted, as [`anagram_distance`]
+    /// counts them. 0 for an exact anagram.
+    pub distance: usize,
+    /// Graphemes of the searched word the candidate lacks, uppercased and
+    /// sorted.
+    pub missing: Vec<String>,
+    /// Graphemes of the candidate the searched word lacks, uppercased and
+    /// sorted.
+    pub extra: Vec<String>,
+}
+
+/// How many graphemes must be added, removed or substituted to turn `a` into
+/// an anagram of `b`, ignoring case. A substitution counts once, so "cat" and
+/// "cot" are 1 apart.
+pub fn anagram_distance(a: &str, b: &str) -> usize {
+    let (only_a, only_b) = diff_anagrams(&convert_to_anagram(a), &convert_to_anagram(b));
+    only_a.len().max(only_b.len())
+}
+
+/// The `possible_anagrams` within `max_distance` of being anagrams of `word`,
+/// closest first and in input order among equals. Like
+/// [`anagrams_for`](super::anagrams_for), `word` itself is left out.
+pub fn near_anagrams_for<'a>(
+    word: &str,
+    possible_anagrams: &[&'a str],
+    max_distance: usize,
+) -> Vec<NearAnagram<'a>> {
+    let word_anagram = convert_to_anagram(word);
+
+    let mut matches: Vec<NearAnagram> = possible_anagrams
+        .iter()
+        .filter(|anagram| !same_word(word, anagram))
+        .filter_map(|&anagram| {
+            let (missing, extra) = diff_anagrams(&word_anagram, &convert_to_anagram(anagram));
+            let distance = missing.len().max(extra.len());
+            (distance <= max_distance).then_some(NearAnagram {
+                word: anagram,
+                distance,
+                missing,
+                extra,
+            })
+        })
+        .collect();
+
+    matches.sort_by_key(|m| m.distance);
+    matches
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn strings(graphemes: &[&str]) -> Vec<String> {
+        graphemes.iter().map(|g| g.to_string()).collect()
+    }
+
+    #[test]
+    fn test_anagram_distance() {
+        assert_eq!(anagram_distance("listen", "Silent"), 0);
+        assert_eq!(anagram_distance("cat", "cot"), 1);
+        assert_eq!(anagram_distance("cat", "cats"), 1);
+        assert_eq!(anagram_distance("cats", "at"), 2);
+        assert_eq!(anagram_distance("", "abc"), 3);
+        assert_eq!(anagram_distance("ΑΒΓ", "γβδ"), 1);
+    }
+
+    #[test]
+    fn test_near_anagrams_ranked_by_distance() {
+        let inputs = ["stone", "notes", "tones", "onset", "tone", "stolen", "ones"];
+        let words: Vec<(&str, usize)> = near_anagrams_for("notes", &inputs, 1)
+            .iter()
+            .map(|m| (m.word, m.distance))
+            .collect();
+        assert_eq!(
+            words,
+            vec![
+                ("stone", 0),
+                ("tones", 0),
+                ("onset", 0),
+                ("tone", 1),
+                ("stolen", 1),
+                ("ones", 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reports_differing_letters() {
+        let matches = near_anagrams_for("heart", &["EARTH", "hearth", "death", "tear"], 1);
+        assert_eq!(
+            matches,
+            vec![
+                NearAnagram {
+                    word: "EARTH",
+                    distance: 0,
+                    missing: vec![],
+                    extra: vec![],
+                },
+                NearAnagram {
+                    word: "hearth",
+                    distance: 1,
+                    missing: vec![],
+                    extra: strings(&["H"]),
+                },
+                NearAnagram {
+                    word: "death",
+                    distance: 1,
+                    missing: strings(&["R"]),
+                    extra: strings(&["D"]),
+                },
+                NearAnagram {
+                    word: "tear",
+                    distance: 1,
+                    missing: strings(&["H"]),
+                    extra: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_max_distance_zero_is_exact() {
+        let inputs = ["tan", "tin", "TAN", "ant"];
+        let matches = near_anagrams_for("ant", &inputs, 0);
+        let words: Vec<&str> = matches.iter().map(|m| m.word).collect();
+        assert_eq!(words, vec!["tan", "TAN"]);
+    }
+}
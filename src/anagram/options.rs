@@ -0,0 +1,262 @@
+//! How words are normalised before their graphemes are compared.
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// How letters of different case are made equal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CaseMapping {
+    /// Uppercase each grapheme on its own, as [`anagrams_for`](super::anagrams_for)
+    /// does, so "ß" becomes the single grapheme "SS".
+    #[default]
+    Upper,
+    /// Unicode default full case folding of the whole word before splitting
+    /// it into graphemes, so "ß" matches "SS" and "ſ" matches "s". Use the
+    /// [`turkish`] locale for Turkic dotted and dotless i.
+    Fold,
+}
+
+/// Unicode normalisation form applied before comparing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Normalization {
+    #[default]
+    None,
+    /// Canonical composition: "e" followed by U+0301 becomes "é".
+    Nfc,
+    /// Canonical decomposition: "é" becomes "e" followed by U+0301.
+    Nfd,
+}
+
+/// Options for anagram matching. The default matches
+/// [`anagrams_for`](super::anagrams_for).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AnagramOptions {
+    pub case: CaseMapping,
+    pub normalization: Normalization,
+    /// Drop combining marks, so "é" matches "e".
+    pub ignore_diacritics: bool,
+    /// Locale-specific rewriting applied to the word first, such as
+    /// [`turkish`].
+    pub locale: Option<fn(&str) -> String>,
+}
+
+impl AnagramOptions {
+    /// The graphemes of `word` after applying the options, in order.
+    pub(super) fn graphemes(&self, word: &str) -> Vec<String> {
+        let mut text = Cow::Borrowed(word);
+
+        if let Some(locale) = self.locale {
+            text = Cow::Owned(locale(&text));
+        }
+        if self.case == CaseMapping::Fold {
+            text = Cow::Owned(fold_case(&text));
+        }
+        if self.ignore_diacritics {
+            text = Cow::Owned(
+                text.nfd()
+                    .filter(|&c| !is_combining_mark(c))
+                    .nfc()
+                    .collect(),
+            );
+        }
+        match self.normalization {
+            Normalization::None => {}
+            Normalization::Nfc => text = Cow::Owned(text.nfc().collect()),
+            Normalization::Nfd => text = Cow::Owned(text.nfd().collect()),
+        }
+
+        text.graphemes(true)
+            .map(|grapheme| match self.case {
+                CaseMapping::Upper => grapheme.to_uppercase(),
+                CaseMapping::Fold => grapheme.to_string(),
+            })
+            .collect()
+    }
+
+    /// The sorted graphemes of `word` after applying the options.
+    pub(super) fn signature(&self, word: &str) -> Vec<String> {
+        let mut result = self.graphemes(word);
+        result.sort_unstable();
+        result
+    }
+
+    /// Whether `a` and `b` are the same word under the options, and so not
+    /// interesting anagrams of each other.
+    pub(super) fn same_word(&self, a: &str, b: &str) -> bool {
+        self.graphemes(a).concat() == self.graphemes(b).concat()
+    }
+}
+
+/// Unicode default full case folding (CaseFolding.txt, statuses C and F),
+/// so "ß" folds to "ss", "ſ" to "s" and "ς" to "σ". The Turkic mappings are
+/// left out: "İ" folds to "i" followed by U+0307 and "ı" stays as it is.
+fn fold_case(text: &str) -> String {
+    caseless::default_case_fold_str(text)
+}
+
+/// Turkish casing, where "i" pairs with "İ" and "ı" with "I".
+pub fn turkish(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'i' => 'İ',
+            'ı' => 'I',
+            c => c,
+        })
+        .collect()
+}
+
+/// Like [`anagrams_for`](super::anagrams_for), comparing words as `options`
+/// describes.
+pub fn anagrams_for_with<'a>(
+    word: &str,
+    possible_anagrams: &[&'a str],
+    options: &AnagramOptions,
+) -> HashSet<&'a str> {
+    let word_anagram = options.signature(word);
+
+    possible_anagrams
+        .iter()
+        .cloned()
+        .filter(|anagram| {
+            options.signature(anagram) == word_anagram && !options.same_word(word, anagram)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::anagrams_for;
+    use super::*;
+
+    const FOLD: AnagramOptions = AnagramOptions {
+        case: CaseMapping::Fold,
+        normalization: Normalization::None,
+        ignore_diacritics: false,
+        locale: None,
+    };
+
+    #[test]
+    fn test_default_matches_anagrams_for() {
+        let inputs = ["ΒΓΑ", "ΒΓΔ", "γβα", "ΑΒγ", "ABΓ", "tan", "TAN", "stand"];
+        for word in ["ΑΒΓ", "ant", "nat", "ßa", ""] {
+            assert_eq!(
+                anagrams_for_with(word, &inputs, &AnagramOptions::default()),
+                anagrams_for(word, &inputs),
+                "{word}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sharp_s_needs_case_folding() {
+        let inputs = ["asserts", "strasse"];
+        assert!(anagrams_for_with("Straße", &inputs, &AnagramOptions::default()).is_empty());
+        assert_eq!(
+            anagrams_for_with("Straße", &inputs, &FOLD),
+            HashSet::from(["asserts"])
+        );
+    }
+
+    #[test]
+    fn test_case_folding_merges_long_s() {
+        assert_eq!(
+            anagrams_for_with("ſtop", &["pots"], &FOLD),
+            HashSet::from(["pots"])
+        );
+    }
+
+    #[test]
+    fn test_case_folding_merges_final_sigma() {
+        assert_eq!(
+            anagrams_for_with("σα", &["ας"], &FOLD),
+            HashSet::from(["ας"])
+        );
+        assert_eq!(
+            anagrams_for_with("ΣΑ", &["ας", "ασ"], &FOLD),
+            HashSet::from(["ας", "ασ"])
+        );
+    }
+
+    #[test]
+    fn test_case_folding_of_dotted_and_dotless_i() {
+        // "İ" folds to "i" with a combining dot above, a single grapheme
+        assert_eq!(
+            anagrams_for_with("İa", &["ai\u{307}", "AI"], &FOLD),
+            HashSet::from(["ai\u{307}"])
+        );
+        // "ı" has no folding, so it stays apart from "I" and "i"
+        assert!(anagrams_for_with("ıa", &["AI", "ai"], &FOLD).is_empty());
+        assert_eq!(
+            anagrams_for_with("ıa", &["AI", "aı"], &FOLD),
+            HashSet::from(["aı"])
+        );
+
+        let turkish_fold = AnagramOptions {
+            locale: Some(turkish),
+            ..FOLD
+        };
+        assert_eq!(
+            anagrams_for_with("ıa", &["AI", "ai"], &turkish_fold),
+            HashSet::from(["AI"])
+        );
+    }
+
+    #[test]
+    fn test_precomposed_and_decomposed_need_normalization() {
+        let inputs = ["cafe\u{301}s"];
+        assert!(anagrams_for_with("\u{e9}cafs", &inputs, &AnagramOptions::default()).is_empty());
+
+        for normalization in [Normalization::Nfc, Normalization::Nfd] {
+            let options = AnagramOptions {
+                normalization,
+                ..Default::default()
+            };
+            assert_eq!(
+                anagrams_for_with("\u{e9}cafs", &inputs, &options),
+                HashSet::from(["cafe\u{301}s"])
+            );
+        }
+    }
+
+    #[test]
+    fn test_ignore_diacritics() {
+        let options = AnagramOptions {
+            ignore_diacritics: true,
+            ..Default::default()
+        };
+        let inputs = ["mesure", "Mesuré", "ésumer"];
+        assert!(anagrams_for_with("résumé", &inputs, &AnagramOptions::default()).is_empty());
+        assert_eq!(
+            anagrams_for_with("résumé", &inputs, &options),
+            HashSet::from(["mesure", "Mesuré", "ésumer"])
+        );
+        assert!(anagrams_for_with("résumé", &["Resume"], &options).is_empty());
+    }
+
+    #[test]
+    fn test_turkish_dotted_and_dotless_i() {
+        let inputs = ["İL", "IL", "ıl"];
+        // by default dotted and dotless lowercase i both uppercase to I
+        assert_eq!(
+            anagrams_for_with("li", &inputs, &AnagramOptions::default()),
+            HashSet::from(["IL", "ıl"])
+        );
+
+        let options = AnagramOptions {
+            locale: Some(turkish),
+            ..Default::default()
+        };
+        assert_eq!(
+            anagrams_for_with("li", &inputs, &options),
+            HashSet::from(["İL"])
+        );
+        assert_eq!(
+            anagrams_for_with("lı", &inputs, &options),
+            HashSet::from(["IL", "ıl"])
+        );
+    }
+}
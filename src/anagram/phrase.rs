@@ -0,0 +1,335 @@
+//! Anagrams of whole phrases, where spaces and punctuation don't count.
+
+use std::collections::HashSet;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{convert_to_anagram, subtract_anagram, AnagramIndex, AnagramOptions};
+
+/// Graphemes left out when comparing phrases.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Ignore {
+    /// Anything that doesn't start with an alphabetic character.
+    #[default]
+    NonAlphabetic,
+    /// Only the listed characters.
+    Chars(&'static [char]),
+}
+
+impl Ignore {
+    pub(super) fn ignores(&self, grapheme: &str) -> bool {
+        match self {
+            Self::NonAlphabetic => !grapheme.chars().next().is_some_and(char::is_alphabetic),
+            Self::Chars(chars) => grapheme
+                .chars()
+                .next()
+                .is_some_and(|c| grapheme.len() == c.len_utf8() && chars.contains(&c)),
+        }
+    }
+}
+
+/// The sorted graphemes of `phrase` that `ignore` keeps.
+fn phrase_anagram(phrase: &str, ignore: Ignore) -> Vec<String> {
+    let mut result = convert_to_anagram(phrase);
+    result.retain(|grapheme| !ignore.ignores(grapheme));
+    result
+}
+
+/// The kept graphemes of `phrase` in their original order, to tell a phrase
+/// apart from a mere respacing of itself.
+fn phrase_letters(phrase: &str, ignore: Ignore) -> String {
+    phrase
+        .graphemes(true)
+        .filter(|grapheme| !ignore.ignores(grapheme))
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Like [`anagrams_for`](super::anagrams_for), but comparing only the
+/// graphemes `ignore` keeps, so "Dormitory" matches "Dirty room".
+pub fn phrase_anagrams_for<'a>(
+    phrase: &str,
+    possible_anagrams: &[&'a str],
+    ignore: Ignore,
+) -> HashSet<&'a str> {
+    let target = phrase_anagram(phrase, ignore);
+    let letters = phrase_letters(phrase, ignore);
+
+    possible_anagrams
+        .iter()
+        .cloned()
+        .filter(|anagram| {
+            phrase_anagram(anagram, ignore) == target && phrase_letters(anagram, ignore) != letters
+        })
+        .collect()
+}
+
+/// Bounds on the search done by [`solve_phrase`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhraseLimits {
+    /// Most words in a single result.
+    pub max_words: usize,
+    /// Fewest kept graphemes in each word.
+    pub min_word_len: usize,
+    /// Most results returned.
+    pub max_results: usize,
+}
+
+impl Default for PhraseLimits {
+    fn default() -> Self {
+        Self {
+            max_words: 3,
+            min_word_len: 1,
+            max_results: 100,
+        }
+    }
+}
+
+struct Search<'a> {
+    /// Dictionary signatures that fit in the phrase, with their words.
+    families: Vec<(Vec<String>, &'a [String])>,
+    limits: PhraseLimits,
+    /// The phrase's own words, mapped as the index maps them and sorted.
+    own_words: Vec<String>,
+    ignore: Ignore,
+    options: AnagramOptions,
+    results: Vec<Vec<&'a str>>,
+}
+
+impl<'a> Search<'a> {
+    /// Pick families from `start` on that use up `remaining` exactly.
+    fn families(&mut self, remaining: &[String], start: usize, chosen: &mut Vec<usize>) {
+        if self.results.len() >= self.limits.max_results {
+            return;
+        }
+        if remaining.is_empty() {
+            self.words(chosen, 0, &mut Vec::new());
+            return;
+        }
+        if chosen.len() == self.limits.max_words {
+            return;
+        }
+        for i in start..self.families.len() {
+            if let Some(rest) = subtract_anagram(remaining, &self.families[i].0) {
+                chosen.push(i);
+                self.families(&rest, i, chosen);
+                chosen.pop();
+            }
+        }
+    }
+
+    /// Expand the chosen families into every combination of their words,
+    /// never taking the same family's words out of dictionary order.
+    fn words(&mut self, chosen: &[usize], from: usize, words: &mut Vec<(usize, &'a str)>) {
+        if self.results.len() >= self.limits.max_results {
+            return;
+        }
+        let Some(&family) = chosen.first() else {
+            let result: Vec<&str> = words.iter().map(|&(_, word)| word).collect();
+            if !self.is_own_phrase(&result) {
+                self.results.push(result);
+            }
+            return;
+        };
+        let family_words = self.families[family].1;
+
+        for (i, word) in family_words.iter().enumerate().skip(from) {
+            words.push((i, word.as_str()));
+            let next_from = if chosen.get(1) == Some(&family) { i } else { 0 };
+            self.words(&chosen[1..], next_from, words);
+            words.pop();
+        }
+    }
+
+    fn is_own_phrase(&self, words: &[&str]) -> bool {
+        let mut words: Vec<String> = words
+            .iter()
+            .map(|word| own_letters(word, self.ignore, &self.options))
+            .collect();
+        words.sort_unstable();
+        words == self.own_words
+    }
+}
+
+/// The kept graphemes of `word` in order, mapped as `options` describes, so
+/// a word only differing in case or accents counts as the same word.
+fn own_letters(word: &str, ignore: Ignore, options: &AnagramOptions) -> String {
+    options
+        .graphemes(word)
+        .into_iter()
+        .filter(|grapheme| !ignore.ignores(grapheme))
+        .collect()
+}
+
+/// Find multi-word anagrams of `phrase` made of `dictionary` words.
+///
+/// Results come in a deterministic order: words within a result follow the
+/// index's signature order, and results are found depth-first in that order.
+/// A result that only rearranges the phrase's own words is skipped.
+pub fn solve_phrase<'a>(
+    phrase: &str,
+    dictionary: &'a AnagramIndex,
+    ignore: Ignore,
+    limits: &PhraseLimits,
+) -> Vec<Vec<&'a str>> {
+    let mut target = dictionary.options().signature(phrase);
+    target.retain(|grapheme| !ignore.ignores(grapheme));
+    let families = dictionary
+        .families()
+        .filter_map(|(signature, words)| {
+            let mut signature = signature.to_vec();
+            signature.retain(|grapheme| !ignore.ignores(grapheme));
+            (!signature.is_empty()
+                && signature.len() >= limits.min_word_len
+                && subtract_anagram(&target, &signature).is_some())
+            .then_some((signature, words))
+        })
+        .collect();
+    let mut own_words: Vec<String> = phrase
+        .split_whitespace()
+        .map(|word| own_letters(word, ignore, dictionary.options()))
+        .filter(|word| !word.is_empty())
+        .collect();
+    own_words.sort_unstable();
+
+    let mut search = Search {
+        families,
+        limits: *limits,
+        own_words,
+        ignore,
+        options: *dictionary.options(),
+        results: Vec::new(),
+    };
+    if !target.is_empty() {
+        search.families(&target, 0, &mut Vec::new());
+    }
+    search.results
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::CaseMapping;
+    use super::*;
+
+    #[test]
+    fn test_phrase_anagrams_ignore_spaces_and_punctuation() {
+        let inputs = ["Dirty room", "dirty-room!", "Dormitory", "Dirty rooms"];
+        assert_eq!(
+            phrase_anagrams_for("Dormitory", &inputs, Ignore::NonAlphabetic),
+            HashSet::from(["Dirty room", "dirty-room!"])
+        );
+    }
+
+    #[test]
+    fn test_phrase_anagram_of_a_longer_phrase() {
+        let inputs = ["I am a weakish speller"];
+        assert_eq!(
+            phrase_anagrams_for("William Shakespeare", &inputs, Ignore::default()),
+            HashSet::from(["I am a weakish speller"])
+        );
+    }
+
+    #[test]
+    fn test_respaced_phrase_is_not_its_own_anagram() {
+        let inputs = ["dirtyroom", "Dirty  Room"];
+        assert!(phrase_anagrams_for("dirty room", &inputs, Ignore::default()).is_empty());
+    }
+
+    #[test]
+    fn test_only_listed_characters_are_ignored() {
+        let inputs = ["dirty room", "dirty-room"];
+        assert_eq!(
+            phrase_anagrams_for("dormitory", &inputs, Ignore::Chars(&[' '])),
+            HashSet::from(["dirty room"])
+        );
+    }
+
+    #[test]
+    fn test_solve_phrase() {
+        let dictionary = AnagramIndex::new(["dirty", "room", "moor", "dormitory", "dirt", "y"]);
+        assert_eq!(
+            solve_phrase(
+                "Dormitory",
+                &dictionary,
+                Ignore::default(),
+                &PhraseLimits::default()
+            ),
+            vec![
+                vec!["dirt", "room", "y"],
+                vec!["dirt", "moor", "y"],
+                vec!["dirty", "room"],
+                vec!["dirty", "moor"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_solve_phrase_limits() {
+        let dictionary = AnagramIndex::new(["dirty", "room", "moor", "dirt", "y"]);
+        let limits = PhraseLimits {
+            max_words: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            solve_phrase("dormitory", &dictionary, Ignore::default(), &limits),
+            vec![vec!["dirty", "room"], vec!["dirty", "moor"]]
+        );
+
+        let limits = PhraseLimits {
+            min_word_len: 2,
+            max_results: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            solve_phrase("dormitory", &dictionary, Ignore::default(), &limits),
+            vec![vec!["dirty", "room"]]
+        );
+    }
+
+    #[test]
+    fn test_solve_phrase_repeats_words_without_duplicates() {
+        let dictionary = AnagramIndex::new(["ab", "ba"]);
+        assert_eq!(
+            solve_phrase(
+                "aabb",
+                &dictionary,
+                Ignore::default(),
+                &PhraseLimits::default()
+            ),
+            vec![vec!["ab", "ab"], vec!["ab", "ba"], vec!["ba", "ba"]]
+        );
+    }
+
+    #[test]
+    fn test_solve_phrase_skips_own_words() {
+        let dictionary = AnagramIndex::new(["dirty", "room", "moor"]);
+        assert_eq!(
+            solve_phrase(
+                "Dirty room",
+                &dictionary,
+                Ignore::default(),
+                &PhraseLimits::default()
+            ),
+            vec![vec!["dirty", "moor"]]
+        );
+    }
+
+    #[test]
+    fn test_own_words_follow_index_options() {
+        let options = AnagramOptions {
+            case: CaseMapping::Fold,
+            ignore_diacritics: true,
+            ..Default::default()
+        };
+        let dictionary = AnagramIndex::with_options(["resume", "room", "moor"], options);
+        assert_eq!(
+            solve_phrase(
+                "RÉSUMÉ Room",
+                &dictionary,
+                Ignore::default(),
+                &PhraseLimits::default()
+            ),
+            vec![vec!["resume", "moor"]]
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Checks on the grapheme multisets of strings, compared as
+//! [`AnagramOptions`] describes. Every grapheme counts, spaces and
+//! punctuation included, except where an [`Ignore`] says otherwise.
+
+use super::{diff_anagrams, subtract_anagram, AnagramOptions, Ignore};
+
+/// Whether the graphemes of `text` that `ignore` keeps can be rearranged
+/// into a palindrome, which needs at most one of them to occur an odd
+/// number of times. The default [`Ignore`] checks phrases letter by letter.
+pub fn can_form_palindrome(text: &str, ignore: Ignore, options: &AnagramOptions) -> bool {
+    let mut signature = options.signature(text);
+    signature.retain(|grapheme| !ignore.ignores(grapheme));
+    let odd = signature
+        .chunk_by(|a, b| a == b)
+        .filter(|run| run.len() % 2 == 1)
+        .count();
+    odd <= 1
+}
+
+/// Whether every grapheme of `part` is in `whole`, as many times as `part`
+/// has it, so `whole` could be rearranged into `part` plus something.
+pub fn is_sub_anagram(part: &str, whole: &str, options: &AnagramOptions) -> bool {
+    subtract_anagram(&options.signature(whole), &options.signature(part)).is_some()
+}
+
+/// The graphemes to add to `a` and to `b` to make them anagrams of each
+/// other, each sorted as the options normalise them.
+pub fn letters_to_add(a: &str, b: &str, options: &AnagramOptions) -> (Vec<String>, Vec<String>) {
+    let (only_a, only_b) = diff_anagrams(&options.signature(a), &options.signature(b));
+    (only_b, only_a)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn strings(graphemes: &[&str]) -> Vec<String> {
+        graphemes.iter().map(|g| g.to_string()).collect()
+    }
+
+    #[test]
+    fn test_can_form_palindrome() {
+        let options = AnagramOptions::default();
+        let ignore = Ignore::default();
+        assert!(can_form_palindrome("carrace", ignore, &options));
+        assert!(can_form_palindrome("Aab", ignore, &options));
+        assert!(can_form_palindrome("", ignore, &options));
+        assert!(!can_form_palindrome("abc", ignore, &options));
+        assert!(can_form_palindrome("tacocat", ignore, &options));
+    }
+
+    #[test]
+    fn test_palindrome_phrases() {
+        let options = AnagramOptions::default();
+        let ignore = Ignore::default();
+        assert!(can_form_palindrome("taco cat", ignore, &options));
+        assert!(can_form_palindrome(
+            "A man, a plan, a canal: Panama!",
+            ignore,
+            &options
+        ));
+        assert!(!can_form_palindrome("taco cats", ignore, &options));
+
+        // counting every grapheme, the lone space is a second odd one
+        let everything = Ignore::Chars(&[]);
+        assert!(!can_form_palindrome("taco cat", everything, &options));
+        assert!(can_form_palindrome("taco  cat", everything, &options));
+    }
+
+    #[test]
+    fn test_palindrome_uses_graphemes_and_options() {
+        let default = AnagramOptions::default();
+        let ignore = Ignore::default();
+        assert!(can_form_palindrome("e\u{301}ae\u{301}", ignore, &default));
+        assert!(!can_form_palindrome("e\u{301}a\u{e9}", ignore, &default));
+
+        let nfc = AnagramOptions {
+            normalization: super::super::Normalization::Nfc,
+            ..Default::default()
+        };
+        assert!(can_form_palindrome("e\u{301}a\u{e9}", ignore, &nfc));
+    }
+
+    #[test]
+    fn test_is_sub_anagram() {
+        let options = AnagramOptions::default();
+        assert!(is_sub_anagram("TEA", "treat", &options));
+        assert!(is_sub_anagram("", "abc", &options));
+        assert!(is_sub_anagram("treat", "treat", &options));
+        assert!(!is_sub_anagram("teat", "eat", &options));
+        assert!(!is_sub_anagram("tee", "treat", &options));
+    }
+
+    #[test]
+    fn test_is_sub_anagram_ignoring_diacritics() {
+        let options = AnagramOptions {
+            ignore_diacritics: true,
+            ..Default::default()
+        };
+        assert!(!is_sub_anagram(
+            "cafe",
+            "façade",
+            &AnagramOptions::default()
+        ));
+        assert!(is_sub_anagram("cafe", "façade", &options));
+    }
+
+    #[test]
+    fn test_letters_to_add() {
+        let options = AnagramOptions::default();
+        assert_eq!(
+            letters_to_add("heart", "Death", &options),
+            (strings(&["D"]), strings(&["R"]))
+        );
+        assert_eq!(
+            letters_to_add("listen", "silent", &options),
+            (vec![], vec![])
+        );
+        assert_eq!(
+            letters_to_add("", "aab", &options),
+            (strings(&["A", "A", "B"]), vec![])
+        );
+    }
+}
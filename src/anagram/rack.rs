@@ -0,0 +1,236 @@
+//! Words that can be formed from a rack of letter tiles, for word games.
+
+use super::{diff_anagrams, AnagramIndex};
+
+/// Rack tile that stands in for any letter.
+pub const BLANK: &str = "?";
+
+/// English Scrabble letter values, 0 for anything else.
+pub fn scrabble_score(letter: &str) -> u32 {
+    match letter {
+        "A" | "E" | "I" | "L" | "N" | "O" | "R" | "S" | "T" | "U" => 1,
+        "D" | "G" => 2,
+        "B" | "C" | "M" | "P" => 3,
+        "F" | "H" | "V" | "W" | "Y" => 4,
+        "K" => 5,
+        "J" | "X" => 8,
+        "Q" | "Z" => 10,
+        _ => 0,
+    }
+}
+
+/// Constraints on the words [`sub_anagrams`] returns.
+#[derive(Debug, Clone, Copy)]
+pub struct RackQuery<'a> {
+    /// Fewest graphemes in a word.
+    pub min_len: usize,
+    /// Most graphemes in a word.
+    pub max_len: usize,
+    /// Text every word must contain, ignoring case.
+    pub must_contain: Option<&'a str>,
+    /// Value of an uppercase grapheme played from a real tile. Blanks score 0.
+    pub letter_score: fn(&str) -> u32,
+}
+
+impl Default for RackQuery<'_> {
+    fn default() -> Self {
+        Self {
+            min_len: 1,
+            max_len: usize::MAX,
+            must_contain: None,
+            letter_score: scrabble_score,
+        }
+    }
+}
+
+/// A dictionary word playable from the rack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RackMatch<'a> {
+    pub word: &'a str,
+    pub score: u32,
+    /// The letters played with blank tiles, uppercased and sorted.
+    pub blanks: Vec<String>,
+}
+
+/// Find the `dictionary` words whose letters are all in `rack`, where each
+/// [`BLANK`] in the rack can stand for one missing letter.
+///
+/// Matches are sorted by score, then length in graphemes, both descending,
+/// then alphabetically.
+pub fn sub_anagrams<'a>(
+    rack: &str,
+    dictionary: &'a AnagramIndex,
+    query: &RackQuery,
+) -> Vec<RackMatch<'a>> {
+    let mut tiles = dictionary.options().signature(rack);
+    let blanks = tiles.iter().filter(|tile| *tile == BLANK).count();
+    tiles.retain(|tile| tile != BLANK);
+    let must_contain = query.must_contain.map(str::to_uppercase);
+
+    let mut matches: Vec<(usize, RackMatch)> = dictionary
+        .families()
+        .filter(|(signature, _)| (query.min_len..=query.max_len).contains(&signature.len()))
+        .filter_map(|(signature, words)| {
+            let (_, missing) = diff_anagrams(&tiles, signature);
+            (missing.len() <= blanks).then(|| {
+                // the signature may be lowercase, as with case folding
+                let score = |graphemes: &[String]| -> u32 {
+                    graphemes
+                        .iter()
+                        .map(|g| (query.letter_score)(&g.to_uppercase()))
+                        .sum()
+                };
+                let score = score(signature) - score(&missing);
+                let mut missing: Vec<String> = missing.iter().map(|g| g.to_uppercase()).collect();
+                missing.sort_unstable();
+                words.iter().map(move |word| {
+                    let found = RackMatch {
+                        word,
+                        score,
+                        blanks: missing.clone(),
+                    };
+                    (signature.len(), found)
+                })
+            })
+        })
+        .flatten()
+        .filter(|(_, m)| {
+            must_contain
+                .as_ref()
+                .is_none_or(|text| m.word.to_uppercase().contains(text.as_str()))
+        })
+        .collect();
+
+    matches.sort_by(|(a_len, a), (b_len, b)| {
+        b.score
+            .cmp(&a.score)
+            .then(b_len.cmp(a_len))
+            .then(a.word.cmp(b.word))
+    });
+    matches.into_iter().map(|(_, m)| m).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{AnagramOptions, CaseMapping};
+    use super::*;
+
+    fn words<'a>(matches: &[RackMatch<'a>]) -> Vec<&'a str> {
+        matches.iter().map(|m| m.word).collect()
+    }
+
+    #[test]
+    fn test_words_from_rack() {
+        let dictionary = AnagramIndex::new(["cat", "act", "tac", "at", "a", "cart", "tact"]);
+        assert_eq!(
+            words(&sub_anagrams("TCA", &dictionary, &RackQuery::default())),
+            vec!["act", "cat", "tac", "at", "a"]
+        );
+    }
+
+    #[test]
+    fn test_scores() {
+        let dictionary = AnagramIndex::new(["quiz", "zit"]);
+        let matches = sub_anagrams("ZIUQT", &dictionary, &RackQuery::default());
+        assert_eq!(matches[0].word, "quiz");
+        assert_eq!(matches[0].score, 22);
+        assert_eq!(matches[1].score, 12);
+    }
+
+    #[test]
+    fn test_blank_tiles() {
+        let dictionary = AnagramIndex::new(["quiz", "quit", "suit"]);
+        let matches = sub_anagrams("QUI?", &dictionary, &RackQuery::default());
+
+        assert_eq!(
+            matches,
+            vec![
+                RackMatch {
+                    word: "quit",
+                    score: 12,
+                    blanks: vec!["T".to_string()]
+                },
+                RackMatch {
+                    word: "quiz",
+                    score: 12,
+                    blanks: vec!["Z".to_string()]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repeated_letters_need_repeated_tiles() {
+        let dictionary = AnagramIndex::new(["book", "bok"]);
+        assert_eq!(
+            words(&sub_anagrams("BOK", &dictionary, &RackQuery::default())),
+            vec!["bok"]
+        );
+        assert_eq!(
+            words(&sub_anagrams("BOK?", &dictionary, &RackQuery::default())),
+            vec!["book", "bok"]
+        );
+    }
+
+    #[test]
+    fn test_lengths_and_must_contain() {
+        let dictionary = AnagramIndex::new(["rating", "gratin", "grin", "ring", "tin", "in"]);
+        let query = RackQuery {
+            min_len: 3,
+            max_len: 4,
+            ..Default::default()
+        };
+        assert_eq!(
+            words(&sub_anagrams("GRATIN", &dictionary, &query)),
+            vec!["grin", "ring", "tin"]
+        );
+
+        let query = RackQuery {
+            must_contain: Some("ING"),
+            ..Default::default()
+        };
+        assert_eq!(
+            words(&sub_anagrams("gratin", &dictionary, &query)),
+            vec!["rating", "ring"]
+        );
+    }
+
+    #[test]
+    fn test_custom_letter_score() {
+        let dictionary = AnagramIndex::new(["ab", "abc"]);
+        let query = RackQuery {
+            letter_score: |_| 1,
+            ..Default::default()
+        };
+        let matches = sub_anagrams("CBA", &dictionary, &query);
+        assert_eq!(words(&matches), vec!["abc", "ab"]);
+        assert_eq!(matches[0].score, 3);
+    }
+
+    #[test]
+    fn test_scores_with_case_folding() {
+        let options = AnagramOptions {
+            case: CaseMapping::Fold,
+            ..Default::default()
+        };
+        let dictionary = AnagramIndex::with_options(["quiz", "zit"], options);
+        let matches = sub_anagrams("ziu?t", &dictionary, &RackQuery::default());
+        assert_eq!(words(&matches), vec!["quiz", "zit"]);
+        assert_eq!(matches[0].score, 12);
+        assert_eq!(matches[0].blanks, vec!["Q".to_string()]);
+        assert_eq!(matches[1].score, 12);
+    }
+
+    #[test]
+    fn test_ties_sorted_by_grapheme_count() {
+        let dictionary = AnagramIndex::new(["\u{e9}\u{e9}", "abc"]);
+        let query = RackQuery {
+            letter_score: |_| 0,
+            ..Default::default()
+        };
+        assert_eq!(
+            words(&sub_anagrams("\u{e9}\u{e9}abc", &dictionary, &query)),
+            vec!["abc", "\u{e9}\u{e9}"]
+        );
+    }
+}
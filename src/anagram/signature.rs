@@ -0,0 +1,266 @@
+//! Anagram checks that count graphemes instead of sorting them, without
+//! allocating for ASCII candidates.
+
+use std::collections::HashSet;
+use std::thread;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::anagram_matches;
+
+/// Below this many candidates [`anagrams_for_parallel`] stays on one thread.
+const PARALLEL_THRESHOLD: usize = 4096;
+
+/// The uppercased graphemes of a word as counts: ASCII letters in a fixed
+/// array, anything else in a short sorted list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Signature {
+    letters: [u32; 26],
+    other: Vec<(String, u32)>,
+    len: usize,
+}
+
+impl Signature {
+    pub(super) fn new(word: &str) -> Self {
+        let mut letters = [0; 26];
+        let mut other: Vec<(String, u32)> = Vec::new();
+        let mut len = 0;
+
+        for grapheme in word.graphemes(true) {
+            len += 1;
+            match ascii_letter(grapheme) {
+                Some(i) => letters[i] += 1,
+                None => {
+                    let upper = grapheme.to_uppercase();
+                    match other.iter_mut().find(|(g, _)| *g == upper) {
+                        Some((_, count)) => *count += 1,
+                        None => other.push((upper, 1)),
+                    }
+                }
+            }
+        }
+        other.sort_unstable();
+        Self {
+            letters,
+            other,
+            len,
+        }
+    }
+
+    /// Whether `candidate` has exactly these graphemes, giving up as soon as
+    /// its length or any letter count is over.
+    pub(super) fn matches(&self, candidate: &str) -> bool {
+        let mut counter = Counter {
+            signature: self,
+            letters: [0; 26],
+            other: Vec::new(),
+            len: 0,
+        };
+
+        if candidate.is_ascii() && !candidate.contains("\r\n") {
+            // every byte is a grapheme of its own
+            if candidate.len() != self.len {
+                return false;
+            }
+            for (i, byte) in candidate.bytes().enumerate() {
+                let counted = if byte.is_ascii_alphabetic() {
+                    counter.add_letter((byte.to_ascii_uppercase() - b'A') as usize)
+                } else {
+                    counter.add(&candidate[i..=i])
+                };
+                if !counted {
+                    return false;
+                }
+            }
+        } else {
+            for grapheme in candidate.graphemes(true) {
+                if !counter.add(grapheme) {
+                    return false;
+                }
+            }
+        }
+        // no count went over, so equal totals mean equal counts
+        counter.len == self.len
+    }
+}
+
+/// Running grapheme counts of a candidate, checked against a [`Signature`].
+struct Counter<'s> {
+    signature: &'s Signature,
+    letters: [u32; 26],
+    other: Vec<u32>,
+    len: usize,
+}
+
+impl Counter<'_> {
+    fn add_letter(&mut self, i: usize) -> bool {
+        self.len += 1;
+        self.letters[i] += 1;
+        self.letters[i] <= self.signature.letters[i]
+    }
+
+    fn add(&mut self, grapheme: &str) -> bool {
+        if let Some(i) = ascii_letter(grapheme) {
+            return self.add_letter(i);
+        }
+        let upper = || grapheme.chars().flat_map(char::to_uppercase);
+        let Ok(i) = self
+            .signature
+            .other
+            .binary_search_by(|(g, _)| g.chars().cmp(upper()))
+        else {
+            return false;
+        };
+        if self.other.is_empty() {
+            self.other.resize(self.signature.other.len(), 0);
+        }
+        self.len += 1;
+        self.other[i] += 1;
+        self.other[i] <= self.signature.other[i].1
+    }
+}
+
+/// The index into the letter counts of a grapheme that uppercases to a
+/// single ASCII letter, such as "a" or "ſ".
+fn ascii_letter(grapheme: &str) -> Option<usize> {
+    let mut upper = grapheme.chars().flat_map(char::to_uppercase);
+    match (upper.next(), upper.next()) {
+        (Some(c @ 'A'..='Z'), None) => Some(c as usize - 'A' as usize),
+        _ => None,
+    }
+}
+
+/// Whether `a` and `b` are equal ignoring case, as `to_uppercase` compares
+/// them, without allocating.
+pub(super) fn same_word(a: &str, b: &str) -> bool {
+    if a.is_ascii() && b.is_ascii() {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a.chars()
+            .flat_map(char::to_uppercase)
+            .eq(b.chars().flat_map(char::to_uppercase))
+    }
+}
+
+/// Like [`anagrams_for`](super::anagrams_for), splitting large candidate
+/// slices across `worker_count` threads.
+pub fn anagrams_for_parallel<'a>(
+    word: &str,
+    possible_anagrams: &[&'a str],
+    worker_count: usize,
+) -> HashSet<&'a str> {
+    let filter = |candidates: &[&'a str]| -> Vec<&'a str> {
+        anagram_matches(word, candidates.iter().cloned())
+            .map(|(_, anagram)| anagram)
+            .collect()
+    };
+
+    if worker_count < 2 || possible_anagrams.len() < PARALLEL_THRESHOLD {
+        return filter(possible_anagrams).into_iter().collect();
+    }
+    thread::scope(|s| {
+        let handles: Vec<_> = possible_anagrams
+            .chunks(possible_anagrams.len().div_ceil(worker_count))
+            .map(|chunk| s.spawn(move || filter(chunk)))
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{anagrams_for, anagrams_for_with, AnagramOptions};
+    use super::*;
+
+    const INPUTS: [&str; 16] = [
+        "tan",
+        "TAN",
+        "nat",
+        "ant ",
+        "ta-n",
+        "stand",
+        "ΒΓΑ",
+        "γβα",
+        "ssa",
+        "ßa",
+        "ſta",
+        "ıtan",
+        "a\r\nb",
+        "b\r\na",
+        "ba\r\n",
+        "e\u{301}a",
+    ];
+
+    #[test]
+    fn test_matches_sorted_graphemes() {
+        let words = [
+            "ant",
+            "ANT",
+            "ΑΒΓ",
+            "ass",
+            "saß",
+            "tas",
+            "itna",
+            "\r\nab",
+            "ae\u{301}",
+            "a",
+            "",
+        ];
+        for word in words {
+            assert_eq!(
+                anagrams_for(word, &INPUTS),
+                anagrams_for_with(word, &INPUTS, &AnagramOptions::default()),
+                "{word:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_rejects_by_letter_count() {
+        let signature = Signature::new("aab");
+        assert!(signature.matches("ABA"));
+        assert!(!signature.matches("abb"));
+        assert!(!signature.matches("aaab"));
+        assert!(!signature.matches("ab"));
+    }
+
+    #[test]
+    fn test_non_ascii_graphemes() {
+        let signature = Signature::new("ßé");
+        assert!(signature.matches("Éß"));
+        assert!(!signature.matches("éss"));
+        assert!(!signature.matches("éé"));
+        assert!(!signature.matches("e\u{301}ß"));
+    }
+
+    #[test]
+    fn test_same_word() {
+        assert!(same_word("Listen", "lISTEN"));
+        assert!(same_word("straße", "STRASSE"));
+        assert!(!same_word("listen", "silent"));
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let candidates: Vec<String> = (0..PARALLEL_THRESHOLD * 2)
+            .map(|i| ["listen", "silent", "enlist", "tinsel", "banana"][i % 5].repeat(i % 3 + 1))
+            .collect();
+        let candidates: Vec<&str> = candidates.iter().map(String::as_str).collect();
+
+        for word in ["inlets", "inletsinlets", "ananab"] {
+            assert_eq!(
+                anagrams_for_parallel(word, &candidates, 4),
+                anagrams_for(word, &candidates),
+                "{word}"
+            );
+        }
+        assert_eq!(
+            anagrams_for_parallel("inlets", &candidates, 0),
+            anagrams_for("inlets", &candidates)
+        );
+    }
+}
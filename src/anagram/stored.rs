@@ -0,0 +1,520 @@
+//! An [`AnagramIndex`] saved to a compact binary file and queried in place.
+//!
+//! All integers are little-endian `u32`s unless noted. The file is:
+//!
+//! - a 32-byte header: the magic `ANAGRIDX`, the format version, the
+//!   options the index was built with, the family and word counts, and an
+//!   FNV-1a `u64` checksum of everything after the header;
+//! - one `(signature offset, first word)` pair per family, plus a final pair
+//!   holding the end of the signature data and the word count;
+//! - one offset per word into the word data, plus the end of the word data;
+//! - the signature data: each family's sorted graphemes, each ended by a
+//!   `0xFF` byte, which never occurs in UTF-8;
+//! - the word data: every word's UTF-8, back to back, family by family.
+//!
+//! Families are stored in the index's signature order, so a lookup is a
+//! binary search that only touches the few entries it compares. Opening an
+//! index only reads its header; [`StoredIndex::verify`] reads the rest.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use super::{AnagramIndex, AnagramOptions, CaseMapping, Normalization};
+
+const MAGIC: &[u8; 8] = b"ANAGRIDX";
+/// The format version written by [`AnagramIndex::write_to`].
+pub const FORMAT_VERSION: u32 = 1;
+const HEADER_LEN: usize = 32;
+const END_OF_GRAPHEME: u8 = 0xFF;
+
+/// Why a stored index couldn't be written or read.
+#[derive(Debug)]
+pub enum StoreError {
+    Io(io::Error),
+    /// The data doesn't start with the index magic bytes.
+    NotAnIndex,
+    /// The file was written by a different version of the format.
+    Version {
+        found: u32,
+        expected: u32,
+    },
+    /// The contents don't match the checksum in the header.
+    Checksum,
+    /// The tables point outside the data or out of order.
+    Corrupt,
+    /// The index uses a locale function, which can't be saved.
+    Locale,
+    /// The index is too big for 32-bit offsets.
+    TooLarge,
+}
+
+impl Display for StoreError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::NotAnIndex => write!(f, "not an anagram index"),
+            Self::Version { found, expected } => write!(
+                f,
+                "anagram index format version {found}, expected version {expected}; rebuild the index"
+            ),
+            Self::Checksum => write!(f, "anagram index checksum mismatch"),
+            Self::Corrupt => write!(f, "anagram index is corrupt"),
+            Self::Locale => write!(f, "an index with a locale function can't be saved"),
+            Self::TooLarge => write!(f, "anagram index too large to save"),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<io::Error> for StoreError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// 64-bit FNV-1a.
+fn checksum(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100_0000_01b3)
+    })
+}
+
+fn encode_options(options: &AnagramOptions) -> Result<u32, StoreError> {
+    if options.locale.is_some() {
+        return Err(StoreError::Locale);
+    }
+    let case = match options.case {
+        CaseMapping::Upper => 0,
+        CaseMapping::Fold => 1,
+    };
+    let normalization = match options.normalization {
+        Normalization::None => 0,
+        Normalization::Nfc => 1,
+        Normalization::Nfd => 2,
+    };
+    Ok(case | normalization << 8 | (options.ignore_diacritics as u32) << 16)
+}
+
+fn decode_options(bits: u32) -> Option<AnagramOptions> {
+    let case = match bits & 0xFF {
+        0 => CaseMapping::Upper,
+        1 => CaseMapping::Fold,
+        _ => return None,
+    };
+    let normalization = match bits >> 8 & 0xFF {
+        0 => Normalization::None,
+        1 => Normalization::Nfc,
+        2 => Normalization::Nfd,
+        _ => return None,
+    };
+    let ignore_diacritics = match bits >> 16 {
+        0 => false,
+        1 => true,
+        _ => return None,
+    };
+    Some(AnagramOptions {
+        case,
+        normalization,
+        ignore_diacritics,
+        locale: None,
+    })
+}
+
+/// Whether `values` never decrease and stay at most `end`.
+fn in_order(values: impl Iterator<Item = usize>, end: usize) -> bool {
+    let mut last = 0;
+    for value in values {
+        if value < last || value > end {
+            return false;
+        }
+        last = value;
+    }
+    true
+}
+
+fn push_u32(buffer: &mut Vec<u8>, value: usize) -> Result<(), StoreError> {
+    let value = u32::try_from(value).map_err(|_| StoreError::TooLarge)?;
+    buffer.extend_from_slice(&value.to_le_bytes());
+    Ok(())
+}
+
+impl AnagramIndex {
+    /// Save the index in the format [`StoredIndex`] reads.
+    pub fn write_to(&self, mut out: impl Write) -> Result<(), StoreError> {
+        let options = encode_options(self.options())?;
+        let (mut families, mut offsets) = (Vec::new(), Vec::new());
+        let (mut signatures, mut words) = (Vec::new(), Vec::new());
+        let mut word_count = 0;
+
+        for (signature, family) in self.families() {
+            push_u32(&mut families, signatures.len())?;
+            push_u32(&mut families, word_count)?;
+            for grapheme in signature {
+                signatures.extend_from_slice(grapheme.as_bytes());
+                signatures.push(END_OF_GRAPHEME);
+            }
+            for word in family {
+                push_u32(&mut offsets, words.len())?;
+                words.extend_from_slice(word.as_bytes());
+            }
+            word_count += family.len();
+        }
+        push_u32(&mut families, signatures.len())?;
+        push_u32(&mut families, word_count)?;
+        push_u32(&mut offsets, words.len())?;
+
+        let family_count = self.families().count();
+        let body = [families, offsets, signatures, words].concat();
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(MAGIC);
+        header.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        header.extend_from_slice(&options.to_le_bytes());
+        push_u32(&mut header, family_count)?;
+        push_u32(&mut header, word_count)?;
+        header.extend_from_slice(&checksum(&body).to_le_bytes());
+
+        out.write_all(&header)?;
+        out.write_all(&body)?;
+        Ok(())
+    }
+}
+
+/// A saved [`AnagramIndex`], queried straight from its bytes, such as a
+/// memory-mapped file from [`StoredIndex::open`].
+#[derive(Debug)]
+pub struct StoredIndex<B> {
+    bytes: B,
+    options: AnagramOptions,
+    family_count: usize,
+    word_count: usize,
+    /// Where each table starts in `bytes`.
+    offsets: usize,
+    signatures: usize,
+    words: usize,
+}
+
+impl StoredIndex<Mmap> {
+    /// Memory-map the index saved at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        let file = File::open(path)?;
+        // SAFETY: the map is only read, and every read is bounds-checked.
+        // Truncating the file while it's mapped is the caller's problem, as
+        // with any mapped file.
+        let map = unsafe { Mmap::map(&file)? };
+        Self::new(map)
+    }
+}
+
+impl<B: AsRef<[u8]>> StoredIndex<B> {
+    /// Check the header of a saved index and that its tables fit in
+    /// `bytes`. Nothing else is read, so this takes the same time however
+    /// big the index is.
+    ///
+    /// A corrupt index that gets this far gives wrong lookups rather than
+    /// panicking; call [`verify`](Self::verify) to rule that out.
+    pub fn new(bytes: B) -> Result<Self, StoreError> {
+        let data = bytes.as_ref();
+        let header = data.get(..HEADER_LEN).ok_or(StoreError::NotAnIndex)?;
+        if &header[..8] != MAGIC {
+            return Err(StoreError::NotAnIndex);
+        }
+        let field = |i: usize| u32::from_le_bytes(header[i..i + 4].try_into().unwrap());
+        let version = field(8);
+        if version != FORMAT_VERSION {
+            return Err(StoreError::Version {
+                found: version,
+                expected: FORMAT_VERSION,
+            });
+        }
+        let options = decode_options(field(12)).ok_or(StoreError::Corrupt)?;
+        let (family_count, word_count) = (field(16) as usize, field(20) as usize);
+
+        let offsets = HEADER_LEN + (family_count + 1) * 8;
+        let signatures = offsets + (word_count + 1) * 4;
+        if signatures > data.len() {
+            return Err(StoreError::Corrupt);
+        }
+        let mut index = Self {
+            bytes,
+            options,
+            family_count,
+            word_count,
+            offsets,
+            signatures,
+            words: 0,
+        };
+        index.words = index.signatures + index.family(family_count).0;
+        if index.words > index.bytes.as_ref().len() {
+            return Err(StoreError::Corrupt);
+        }
+        Ok(index)
+    }
+
+    /// Check the checksum and every table entry, reading the whole index.
+    pub fn verify(&self) -> Result<(), StoreError> {
+        let data = self.bytes.as_ref();
+        let expected = u64::from_le_bytes(data[24..32].try_into().unwrap());
+        if checksum(&data[HEADER_LEN..]) != expected {
+            return Err(StoreError::Checksum);
+        }
+
+        let words_len = data.len() - self.words;
+        let families = (0..=self.family_count).map(|i| self.family(i));
+        let word_offsets = (0..=self.word_count).map(|i| self.u32_at(self.offsets + i * 4));
+
+        let valid = in_order(families.clone().map(|(sig, _)| sig), usize::MAX)
+            && in_order(families.map(|(_, word)| word), self.word_count)
+            && self.family(self.family_count).1 == self.word_count
+            && in_order(word_offsets, words_len)
+            && self.u32_at(self.offsets + self.word_count * 4) == words_len;
+        if !valid {
+            return Err(StoreError::Corrupt);
+        }
+        let words = std::str::from_utf8(&data[self.words..]).map_err(|_| StoreError::Corrupt)?;
+        (0..self.word_count)
+            .all(|i| words.is_char_boundary(self.u32_at(self.offsets + i * 4)))
+            .then_some(())
+            .ok_or(StoreError::Corrupt)
+    }
+
+    /// The entry at `at`, which must be inside the tables `new` checked.
+    fn u32_at(&self, at: usize) -> usize {
+        let bytes = &self.bytes.as_ref()[at..at + 4];
+        u32::from_le_bytes(bytes.try_into().unwrap()) as usize
+    }
+
+    /// Signature data offset and first word of family `i`.
+    fn family(&self, i: usize) -> (usize, usize) {
+        let at = HEADER_LEN + i * 8;
+        (self.u32_at(at), self.u32_at(at + 4))
+    }
+
+    /// The graphemes of family `i`'s signature, as UTF-8 bytes, or none if
+    /// the table points outside the signature data.
+    fn signature(&self, i: usize) -> impl Iterator<Item = &[u8]> {
+        let (start, end) = (self.family(i).0, self.family(i + 1).0);
+        let data = self.bytes.as_ref()[self.signatures..self.words]
+            .get(start..end)
+            .unwrap_or_default();
+        data.split(|&byte| byte == END_OF_GRAPHEME)
+            .take(data.iter().filter(|&&byte| byte == END_OF_GRAPHEME).count())
+    }
+
+    /// The words of family `i`, with any the tables get wrong left empty.
+    fn words(&self, i: usize) -> impl Iterator<Item = &str> {
+        let first = self.family(i).1.min(self.word_count);
+        let next = self.family(i + 1).1.min(self.word_count);
+        (first..next).map(|word| {
+            let start = self.u32_at(self.offsets + word * 4);
+            let end = self.u32_at(self.offsets + word * 4 + 4);
+            self.bytes.as_ref()[self.words..]
+                .get(start..end)
+                .and_then(|word| std::str::from_utf8(word).ok())
+                .unwrap_or_default()
+        })
+    }
+
+    /// The options the saved index was built with.
+    pub fn options(&self) -> &AnagramOptions {
+        &self.options
+    }
+
+    /// The saved words that are anagrams of `word`, as
+    /// [`AnagramIndex::lookup`] finds them.
+    pub fn lookup(&self, word: &str) -> HashSet<&str> {
+        let target = self.options.signature(word);
+        let (mut low, mut high) = (0, self.family_count);
+
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let order = self
+                .signature(mid)
+                .cmp(target.iter().map(|grapheme| grapheme.as_bytes()));
+            match order {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => {
+                    return self
+                        .words(mid)
+                        .filter(|candidate| !self.options.same_word(word, candidate))
+                        .collect()
+                }
+            }
+        }
+        HashSet::new()
+    }
+
+    /// Number of words in the index.
+    pub fn len(&self) -> usize {
+        self.word_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.word_count == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DICTIONARY: [&str; 10] = [
+        "enlists", "google", "inlets", "banana", "Listen", "silent", "tinsel", "ΒΓΑ", "γβα", "a\0b",
+    ];
+
+    fn saved(index: &AnagramIndex) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        index.write_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_lookup_matches_index() {
+        let index = AnagramIndex::new(DICTIONARY);
+        let stored = StoredIndex::new(saved(&index)).unwrap();
+
+        assert_eq!(stored.len(), index.len());
+        for word in [
+            "listen",
+            "ΑΒΓ",
+            "banana",
+            "b\0a",
+            "zzz",
+            "",
+            "a",
+            "\u{10FFFF}",
+        ] {
+            assert_eq!(stored.lookup(word), index.lookup(word), "{word:?}");
+        }
+    }
+
+    #[test]
+    fn test_options_are_saved() {
+        let options = AnagramOptions {
+            case: CaseMapping::Fold,
+            ignore_diacritics: true,
+            ..Default::default()
+        };
+        let index = AnagramIndex::with_options(["Mésuré", "asserts"], options);
+        let bytes = saved(&index);
+        let stored = StoredIndex::new(bytes.as_slice()).unwrap();
+
+        assert_eq!(stored.lookup("resume"), HashSet::from(["Mésuré"]));
+        assert_eq!(stored.lookup("Straße"), HashSet::from(["asserts"]));
+    }
+
+    #[test]
+    fn test_locale_cannot_be_saved() {
+        let options = AnagramOptions {
+            locale: Some(super::super::turkish),
+            ..Default::default()
+        };
+        let index = AnagramIndex::with_options(["a"], options);
+        assert!(matches!(
+            index.write_to(Vec::new()),
+            Err(StoreError::Locale)
+        ));
+    }
+
+    #[test]
+    fn test_empty_index() {
+        let stored = StoredIndex::new(saved(&AnagramIndex::default())).unwrap();
+        assert!(stored.is_empty());
+        assert!(stored.lookup("anything").is_empty());
+    }
+
+    #[test]
+    fn test_version_mismatch() {
+        let mut bytes = saved(&AnagramIndex::new(DICTIONARY));
+        bytes[8] = 9;
+        let err = StoredIndex::new(bytes).unwrap_err();
+
+        assert!(matches!(
+            err,
+            StoreError::Version {
+                found: 9,
+                expected: FORMAT_VERSION
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "anagram index format version 9, expected version 1; rebuild the index"
+        );
+    }
+
+    /// Store the right checksum for corrupted `bytes`.
+    fn with_checksum(mut bytes: Vec<u8>) -> Vec<u8> {
+        let sum = checksum(&bytes[HEADER_LEN..]);
+        bytes[24..32].copy_from_slice(&sum.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_verify() {
+        let bytes = saved(&AnagramIndex::new(DICTIONARY));
+        assert!(StoredIndex::new(bytes.as_slice()).unwrap().verify().is_ok());
+
+        let mut flipped = bytes.clone();
+        *flipped.last_mut().unwrap() ^= 1;
+        let stored = StoredIndex::new(flipped).unwrap();
+        assert!(matches!(stored.verify(), Err(StoreError::Checksum)));
+
+        let stored = StoredIndex::new(&bytes[..bytes.len() - 1]).unwrap();
+        assert!(matches!(stored.verify(), Err(StoreError::Checksum)));
+
+        // first family's signature offset past the signature data
+        let mut tables = bytes.clone();
+        tables[HEADER_LEN..HEADER_LEN + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let stored = StoredIndex::new(with_checksum(tables)).unwrap();
+        assert!(matches!(stored.verify(), Err(StoreError::Corrupt)));
+    }
+
+    #[test]
+    fn test_corrupt_tables_do_not_panic_lookups() {
+        let bytes = saved(&AnagramIndex::new(DICTIONARY));
+        let tables_end = HEADER_LEN + (bytes.len() - HEADER_LEN) / 2;
+
+        for at in HEADER_LEN..tables_end {
+            for value in [0, 0x7F, 0xFF] {
+                let mut corrupt = bytes.clone();
+                corrupt[at] = value;
+                if let Ok(stored) = StoredIndex::new(corrupt) {
+                    for word in ["listen", "ΑΒΓ", "banana", "zzz"] {
+                        stored.lookup(word);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_bad_data() {
+        let bytes = saved(&AnagramIndex::new(DICTIONARY));
+        assert!(matches!(
+            StoredIndex::new(&b"not an index at all, no no no no"[..]),
+            Err(StoreError::NotAnIndex)
+        ));
+        assert!(matches!(
+            StoredIndex::new(&bytes[..10]),
+            Err(StoreError::NotAnIndex)
+        ));
+    }
+
+    #[test]
+    fn test_open_mapped_file() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let index = AnagramIndex::new(DICTIONARY);
+        index.write_to(file.as_file()).unwrap();
+
+        let stored = StoredIndex::open(file.path()).unwrap();
+        assert!(stored.verify().is_ok());
+        assert_eq!(stored.lookup("Listen"), index.lookup("Listen"));
+    }
+}
@@ -0,0 +1,300 @@
+//! Find anagrams, sub-anagrams or multi-word phrase anagrams of a word in a
+//! dictionary file.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::process::ExitCode;
+
+use rust_exercism::anagram::{
+    self, AnagramIndex, AnagramOptions, CaseMapping, Ignore, PhraseLimits, RackQuery,
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+const USAGE: &str = "\
+usage: anagram [options] WORD DICTIONARY
+
+Reads DICTIONARY, or stdin when it is '-', one word per line, and prints
+the anagrams of WORD found in it, one per line.
+
+options:
+  -s, --sub              print words made from some of WORD's letters,
+                         where '?' in WORD stands for any letter
+  -p, --phrase           print phrases of dictionary words that use all
+                         of WORD's letters, ignoring spaces and punctuation
+  -f, --fold             use full case folding, so 'ß' matches 'ss'
+  -a, --ignore-accents   treat letters with diacritics as plain letters
+  -m, --min-length N     skip words shorter than N letters
+  -w, --max-words N      most words in a phrase (default 3)
+  -j, --json             print a JSON array instead
+  -h, --help             print this message
+
+Exits with 1 if nothing was found, 2 on usage or I/O errors.";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Mode {
+    #[default]
+    Exact,
+    Sub,
+    Phrase,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    mode: Mode,
+    fold: bool,
+    ignore_accents: bool,
+    min_length: usize,
+    max_words: Option<usize>,
+    json: bool,
+    word: String,
+    dictionary: String,
+}
+
+fn parse_count(value: Option<String>, flag: &str) -> Result<usize, String> {
+    let value = value.ok_or(format!("{flag} needs a value"))?;
+    match value.parse() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err(format!("invalid {flag}: {value}")),
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut positional = Vec::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-s" | "--sub" => options.mode = Mode::Sub,
+            "-p" | "--phrase" => options.mode = Mode::Phrase,
+            "-f" | "--fold" => options.fold = true,
+            "-a" | "--ignore-accents" => options.ignore_accents = true,
+            "-m" | "--min-length" => options.min_length = parse_count(args.next(), "--min-length")?,
+            "-w" | "--max-words" => {
+                options.max_words = Some(parse_count(args.next(), "--max-words")?)
+            }
+            "-j" | "--json" => options.json = true,
+            "-h" | "--help" => return Err(String::new()),
+            flag if flag.starts_with('-') && flag != "-" => {
+                return Err(format!("unknown option: {flag}"))
+            }
+            _ => positional.push(arg),
+        }
+    }
+    if options.max_words.is_some() && options.mode != Mode::Phrase {
+        return Err("--max-words needs --phrase".to_string());
+    }
+    let [word, dictionary]: [String; 2] = positional
+        .try_into()
+        .map_err(|_| "expected a word and a dictionary".to_string())?;
+    options.word = word;
+    options.dictionary = dictionary;
+    Ok(options)
+}
+
+fn load(input: impl BufRead, options: &Options) -> io::Result<AnagramIndex> {
+    let anagram_options = AnagramOptions {
+        case: if options.fold {
+            CaseMapping::Fold
+        } else {
+            CaseMapping::Upper
+        },
+        ignore_diacritics: options.ignore_accents,
+        ..Default::default()
+    };
+    let words = input.lines().collect::<io::Result<Vec<String>>>()?;
+    let words = words.iter().map(|w| w.trim()).filter(|w| !w.is_empty());
+    Ok(AnagramIndex::with_options(words, anagram_options))
+}
+
+/// Every result as its words: one for exact and sub-anagrams, several for
+/// phrases.
+fn find<'a>(index: &'a AnagramIndex, options: &Options) -> Vec<Vec<&'a str>> {
+    let long_enough = |word: &&str| word.graphemes(true).count() >= options.min_length;
+
+    match options.mode {
+        Mode::Exact => {
+            let mut words: Vec<&str> = index
+                .lookup(&options.word)
+                .into_iter()
+                .filter(long_enough)
+                .collect();
+            words.sort_unstable();
+            words.into_iter().map(|word| vec![word]).collect()
+        }
+        Mode::Sub => {
+            let query = RackQuery {
+                min_len: options.min_length.max(1),
+                ..Default::default()
+            };
+            anagram::sub_anagrams(&options.word, index, &query)
+                .into_iter()
+                .map(|m| vec![m.word])
+                .collect()
+        }
+        Mode::Phrase => {
+            let defaults = PhraseLimits::default();
+            let limits = PhraseLimits {
+                max_words: options.max_words.unwrap_or(defaults.max_words),
+                min_word_len: options.min_length.max(1),
+                ..defaults
+            };
+            anagram::solve_phrase(&options.word, index, Ignore::default(), &limits)
+        }
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn print(results: &[Vec<&str>], options: &Options, out: &mut impl Write) -> io::Result<()> {
+    if !options.json {
+        for words in results {
+            writeln!(out, "{}", words.join(" "))?;
+        }
+        return Ok(());
+    }
+    let items: Vec<String> = results
+        .iter()
+        .map(|words| {
+            let words: Vec<String> = words.iter().map(|w| json_string(w)).collect();
+            match options.mode {
+                Mode::Phrase => format!("[{}]", words.join(",")),
+                Mode::Exact | Mode::Sub => words.concat(),
+            }
+        })
+        .collect();
+    writeln!(out, "[{}]", items.join(","))
+}
+
+fn run(options: &Options) -> io::Result<bool> {
+    let index = if options.dictionary == "-" {
+        load(io::stdin().lock(), options)?
+    } else {
+        load(BufReader::new(File::open(&options.dictionary)?), options)?
+    };
+    let results = find(&index, options);
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    print(&results, options, &mut out)?;
+    out.flush()?;
+    Ok(!results.is_empty())
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) if err.is_empty() => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("anagram: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&options) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(err) => {
+            eprintln!("anagram: {}: {err}", options.dictionary);
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DICTIONARY: &str = "inlets\nListen\nsilent\ntin\nlist\n\nDirty\nroom\nmoor\nrésumé\n";
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        args.iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    fn output(arguments: &[&str]) -> String {
+        let options = parse_args(args(arguments)).unwrap();
+        let index = load(DICTIONARY.as_bytes(), &options).unwrap();
+        let mut out = Vec::new();
+        print(&find(&index, &options), &options, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(args(&["-p", "-w", "2", "-a", "dormitory", "words.txt"])),
+            Ok(Options {
+                mode: Mode::Phrase,
+                ignore_accents: true,
+                max_words: Some(2),
+                word: "dormitory".to_string(),
+                dictionary: "words.txt".to_string(),
+                ..Default::default()
+            })
+        );
+        assert!(parse_args(args(&["word"])).is_err());
+        assert!(parse_args(args(&["a", "b", "c"])).is_err());
+        assert!(parse_args(args(&["-m", "0", "a", "b"])).is_err());
+        assert!(parse_args(args(&["-w", "2", "a", "b"])).is_err());
+        assert!(parse_args(args(&["--bogus", "a", "b"])).is_err());
+    }
+
+    #[test]
+    fn test_exact_anagrams() {
+        assert_eq!(output(&["enlist", "-"]), "Listen\ninlets\nsilent\n");
+        assert_eq!(output(&["zzz", "-"]), "");
+    }
+
+    #[test]
+    fn test_sub_anagrams_with_min_length() {
+        assert_eq!(output(&["-s", "-m", "4", "tinsl", "-"]), "list\n");
+        assert_eq!(output(&["--sub", "tinsl", "-"]), "list\ntin\n");
+    }
+
+    #[test]
+    fn test_phrase_anagrams() {
+        assert_eq!(
+            output(&["-p", "Dormitory", "-"]),
+            "Dirty room\nDirty moor\n"
+        );
+    }
+
+    #[test]
+    fn test_ignore_accents() {
+        assert_eq!(output(&["mesure", "-"]), "");
+        assert_eq!(output(&["-a", "mesure", "-"]), "résumé\n");
+    }
+
+    #[test]
+    fn test_json_output() {
+        assert_eq!(
+            output(&["-j", "enlist", "-"]),
+            "[\"Listen\",\"inlets\",\"silent\"]\n"
+        );
+        assert_eq!(
+            output(&["--json", "-p", "dormitory", "-"]),
+            "[[\"Dirty\",\"room\"],[\"Dirty\",\"moor\"]]\n"
+        );
+        assert_eq!(json_string("a\"b\\\u{1}"), "\"a\\\"b\\\\\\u0001\"");
+    }
+}
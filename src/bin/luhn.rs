@@ -0,0 +1,237 @@
+//! Validate Luhn numbers from files or stdin, one per line or from a CSV
+//! column, or append check digits to them.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::process::ExitCode;
+
+use rust_exercism::luhn;
+
+const USAGE: &str = "\
+usage: luhn [options] [FILE...]
+
+Reads numbers from each FILE, or stdin when none is given, one per line.
+
+options:
+  -c, --check-digit      append the check digit to each number instead
+  -k, --column N         read the number from CSV column N (1-based)
+  -d, --delimiter CHAR   CSV delimiter (default ',')
+      --header           skip the first line of each input
+  -q, --quiet            only report failing lines
+  -h, --help             print this message
+
+Exits with 1 if any line fails, 2 on usage or I/O errors.";
+
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    check_digit: bool,
+    column: Option<usize>,
+    delimiter: Option<char>,
+    header: bool,
+    quiet: bool,
+    files: Vec<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-c" | "--check-digit" => options.check_digit = true,
+            "-k" | "--column" => {
+                let value = args.next().ok_or("--column needs a value")?;
+                options.column = match value.parse() {
+                    Ok(column) if column > 0 => Some(column),
+                    _ => return Err(format!("invalid column: {value}")),
+                };
+            }
+            "-d" | "--delimiter" => {
+                let value = args.next().ok_or("--delimiter needs a value")?;
+                let mut chars = value.chars();
+                options.delimiter = match (chars.next(), chars.next()) {
+                    (Some(c), None) => Some(c),
+                    _ => return Err(format!("invalid delimiter: {value}")),
+                };
+            }
+            "--header" => options.header = true,
+            "-q" | "--quiet" => options.quiet = true,
+            "-h" | "--help" => return Err(String::new()),
+            flag if flag.starts_with('-') && flag != "-" => {
+                return Err(format!("unknown option: {flag}"))
+            }
+            _ => options.files.push(arg),
+        }
+    }
+    if options.delimiter.is_some() && options.column.is_none() {
+        return Err("--delimiter needs --column".to_string());
+    }
+    Ok(options)
+}
+
+/// The number to check on `line`: the whole line, or a CSV field with
+/// surrounding whitespace and quotes trimmed.
+fn field<'a>(line: &'a str, options: &Options) -> Option<&'a str> {
+    match options.column {
+        None => Some(line),
+        Some(column) => line
+            .split(options.delimiter.unwrap_or(','))
+            .nth(column - 1)
+            .map(|field| field.trim().trim_matches('"')),
+    }
+}
+
+/// Report on every line of `input`, returning whether they all passed.
+fn process(
+    name: &str,
+    input: impl BufRead,
+    options: &Options,
+    out: &mut impl Write,
+) -> io::Result<bool> {
+    let mut all_ok = true;
+
+    for (i, line) in input.lines().enumerate().skip(options.header as usize) {
+        let line = line?;
+        let line_no = i + 1;
+        let result = match field(&line, options) {
+            None => Err("missing column".to_string()),
+            Some(number) if options.check_digit => luhn::check_digit(number)
+                .map(|check| format!("{number}{check}"))
+                .ok_or_else(|| format!("{number}\tcannot compute a check digit")),
+            Some(number) => luhn::validate(number)
+                .map(|()| format!("{number}\tvalid"))
+                .map_err(|err| format!("{number}\tinvalid: {err}")),
+        };
+
+        match result {
+            Ok(report) if !options.quiet => writeln!(out, "{name}:{line_no}\t{report}")?,
+            Ok(_) => {}
+            Err(report) => {
+                all_ok = false;
+                writeln!(out, "{name}:{line_no}\t{report}")?;
+            }
+        }
+    }
+    Ok(all_ok)
+}
+
+fn run(options: &Options) -> io::Result<bool> {
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut all_ok = true;
+
+    if options.files.is_empty() {
+        all_ok &= process("-", io::stdin().lock(), options, &mut out)?;
+    }
+    for name in &options.files {
+        all_ok &= if name == "-" {
+            process(name, io::stdin().lock(), options, &mut out)?
+        } else {
+            process(name, BufReader::new(File::open(name)?), options, &mut out)?
+        };
+    }
+    out.flush()?;
+    Ok(all_ok)
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) if err.is_empty() => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("luhn: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&options) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(err) => {
+            eprintln!("luhn: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        args.iter()
+            .map(|arg| arg.to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    fn report(input: &str, options: &Options) -> (String, bool) {
+        let mut out = Vec::new();
+        let ok = process("-", input.as_bytes(), options, &mut out).unwrap();
+        (String::from_utf8(out).unwrap(), ok)
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(args(&["-k", "2", "-d", ";", "--header", "a.csv"])),
+            Ok(Options {
+                column: Some(2),
+                delimiter: Some(';'),
+                header: true,
+                files: vec!["a.csv".to_string()],
+                ..Default::default()
+            })
+        );
+        assert!(parse_args(args(&["-k", "0"])).is_err());
+        assert!(parse_args(args(&["-d", ";"])).is_err());
+        assert!(parse_args(args(&["--bogus"])).is_err());
+    }
+
+    #[test]
+    fn test_reports_each_line() {
+        let (out, ok) = report("059\n055-444-285\n", &Options::default());
+        assert!(!ok);
+        assert_eq!(
+            out,
+            "-:1\t059\tvalid\n-:2\t055-444-285\tinvalid: invalid character '-' at position 3\n"
+        );
+    }
+
+    #[test]
+    fn test_quiet_only_reports_failures() {
+        let options = Options {
+            quiet: true,
+            ..Default::default()
+        };
+        assert_eq!(report("059\n59\n", &options), (String::new(), true));
+    }
+
+    #[test]
+    fn test_csv_column() {
+        let options = Options {
+            column: Some(2),
+            header: true,
+            ..Default::default()
+        };
+        let (out, ok) = report("id,card\n1,\"4539 3195 0343 6467\"\n2\n", &options);
+        assert!(!ok);
+        assert_eq!(
+            out,
+            "-:2\t4539 3195 0343 6467\tvalid\n-:3\tmissing column\n"
+        );
+    }
+
+    #[test]
+    fn test_check_digits() {
+        let options = Options {
+            check_digit: true,
+            ..Default::default()
+        };
+        let (out, ok) = report("05\n055 444 28\n", &options);
+        assert!(ok);
+        assert_eq!(out, "-:1\t059\n-:2\t055 444 285\n");
+    }
+}
@@ -0,0 +1,117 @@
+//! GS1 check digits for EAN-8, UPC-A, EAN-13 and GTIN-14.
+//!
+//! Starting from the digit next to the check digit and moving left, digits are
+//! weighted 3, 1, 3, 1, ... and the check digit brings the sum up to a
+//! multiple of 10.
+//!
+//! ```
+//! use rust_exercism::gs1::{self, Gtin};
+//!
+//! assert_eq!(gs1::kind("4006381333931"), Some(Gtin::Ean13));
+//! assert_eq!(gs1::check_digit("400638133393"), Some(1));
+//! ```
+
+/// The GS1 identifiers, told apart by their length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gtin {
+    Ean8,
+    UpcA,
+    Ean13,
+    Gtin14,
+}
+
+impl Gtin {
+    /// Number of digits, check digit included.
+    pub fn digit_count(&self) -> usize {
+        match self {
+            Self::Ean8 => 8,
+            Self::UpcA => 12,
+            Self::Ean13 => 13,
+            Self::Gtin14 => 14,
+        }
+    }
+
+    fn from_len(len: usize) -> Option<Self> {
+        match len {
+            8 => Some(Self::Ean8),
+            12 => Some(Self::UpcA),
+            13 => Some(Self::Ean13),
+            14 => Some(Self::Gtin14),
+            _ => None,
+        }
+    }
+}
+
+/// Weighted sum of the digits in `code`, with the rightmost one weighted by
+/// `first_weight`, along with the number of digits seen.
+fn checksum(code: &str, first_weight: u32) -> Option<(u32, usize)> {
+    code.chars()
+        .rev()
+        .filter(|c| !c.is_whitespace())
+        .try_fold((0, 0), |(sum, count), val| {
+            let weight = if count % 2 == 0 {
+                first_weight
+            } else {
+                4 - first_weight
+            };
+            val.to_digit(10).map(|num| (sum + num * weight, count + 1))
+        })
+}
+
+/// Detect which GS1 identifier `code` is, if it is a valid one.
+pub fn kind(code: &str) -> Option<Gtin> {
+    checksum(code, 1)
+        .filter(|&(sum, _)| sum % 10 == 0)
+        .and_then(|(_, count)| Gtin::from_len(count))
+}
+
+/// Check an EAN-8, UPC-A, EAN-13 or GTIN-14 check digit.
+pub fn is_valid(code: &str) -> bool {
+    kind(code).is_some()
+}
+
+/// Compute the digit that, appended to `code`, makes it pass [`is_valid`].
+pub fn check_digit(code: &str) -> Option<u8> {
+    checksum(code, 3)
+        .filter(|&(_, count)| count > 0)
+        .map(|(sum, _)| ((10 - sum % 10) % 10) as u8)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_valid_identifiers_of_each_kind() {
+        assert_eq!(kind("7351 3537"), Some(Gtin::Ean8));
+        assert_eq!(kind("036000291452"), Some(Gtin::UpcA));
+        assert_eq!(kind("4006381333931"), Some(Gtin::Ean13));
+        assert_eq!(kind("10012345678902"), Some(Gtin::Gtin14));
+    }
+
+    #[test]
+    fn test_wrong_check_digit_is_invalid() {
+        assert!(!is_valid("4006381333932"));
+        assert!(!is_valid("036000291453"));
+    }
+
+    #[test]
+    fn test_unsupported_length_is_invalid() {
+        // valid weighted sum, but no GS1 identifier has 10 digits
+        assert!(!is_valid("0000000000"));
+    }
+
+    #[test]
+    fn test_non_digits_are_invalid() {
+        assert!(!is_valid("40063813339a1"));
+        assert!(!is_valid("4006-3813-33931"));
+    }
+
+    #[test]
+    fn test_check_digit() {
+        assert_eq!(check_digit("400638133393"), Some(1));
+        assert_eq!(check_digit("03600029145"), Some(2));
+        assert_eq!(check_digit("7351353"), Some(7));
+        assert_eq!(check_digit(""), None);
+    }
+}
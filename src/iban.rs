@@ -0,0 +1,128 @@
+//! International Bank Account Numbers (ISO 13616), checked mod 97.
+//!
+//! The first four characters are moved to the end, letters are replaced by
+//! two digits (A = 10, ..., Z = 35) and the resulting number must leave a
+//! remainder of 1 when divided by 97.
+//!
+//! ```
+//! use rust_exercism::iban;
+//!
+//! assert!(iban::is_valid("GB82 WEST 1234 5698 7654 32"));
+//! assert_eq!(iban::check_digits("GB", "WEST12345698765432"), Some(82));
+//! ```
+
+/// IBAN length for each country in the SWIFT registry, sorted by country.
+#[rustfmt::skip]
+const LENGTHS: &[(&str, usize)] = &[
+    ("AD", 24), ("AE", 23), ("AL", 28), ("AT", 20), ("AZ", 28), ("BA", 20), ("BE", 16), ("BG", 22),
+    ("BH", 22), ("BI", 27), ("BR", 29), ("BY", 28), ("CH", 21), ("CR", 22), ("CY", 28), ("CZ", 24),
+    ("DE", 22), ("DJ", 27), ("DK", 18), ("DO", 28), ("EE", 20), ("EG", 29), ("ES", 24), ("FI", 18),
+    ("FK", 18), ("FO", 18), ("FR", 27), ("GB", 22), ("GE", 22), ("GI", 23), ("GL", 18), ("GR", 27),
+    ("GT", 28), ("HR", 21), ("HU", 28), ("IE", 22), ("IL", 23), ("IQ", 23), ("IS", 26), ("IT", 27),
+    ("JO", 30), ("KW", 30), ("KZ", 20), ("LB", 28), ("LC", 32), ("LI", 21), ("LT", 20), ("LU", 20),
+    ("LV", 21), ("LY", 25), ("MC", 27), ("MD", 24), ("ME", 22), ("MK", 19), ("MN", 20), ("MR", 27),
+    ("MT", 31), ("MU", 30), ("NI", 28), ("NL", 18), ("NO", 15), ("OM", 23), ("PK", 24), ("PL", 28),
+    ("PS", 29), ("PT", 25), ("QA", 29), ("RO", 24), ("RS", 22), ("RU", 33), ("SA", 24), ("SC", 31),
+    ("SD", 18), ("SE", 24), ("SI", 19), ("SK", 24), ("SM", 27), ("SO", 23), ("ST", 25), ("SV", 28),
+    ("TL", 23), ("TN", 24), ("TR", 26), ("UA", 29), ("VA", 22), ("VG", 24), ("XK", 20), ("YE", 30),
+];
+
+/// IBAN length for `country`, if it issues IBANs.
+pub fn country_length(country: &str) -> Option<usize> {
+    LENGTHS
+        .binary_search_by_key(&country, |&(code, _)| code)
+        .ok()
+        .map(|i| LENGTHS[i].1)
+}
+
+/// Remainder mod 97 of `code` with its letters expanded to two digits,
+/// computed digit by digit so any length fits.
+fn mod97(code: impl Iterator<Item = char>) -> Option<u32> {
+    code.map(|c| c.to_digit(36)).try_fold(0, |rem, num| {
+        num.map(|num| {
+            if num > 9 {
+                (rem * 100 + num) % 97
+            } else {
+                (rem * 10 + num) % 97
+            }
+        })
+    })
+}
+
+/// Drop spaces and uppercase, as IBANs are often printed in groups of four.
+fn strip(code: &str) -> String {
+    code.chars()
+        .filter(|c| *c != ' ')
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Check an IBAN, e.g. "GB82 WEST 1234 5698 7654 32".
+pub fn is_valid(code: &str) -> bool {
+    let code = strip(code);
+
+    code.is_ascii()
+        && code.len() > 4
+        && country_length(&code[..2]) == Some(code.len())
+        && code[2..4].bytes().all(|b| b.is_ascii_digit())
+        && mod97(code[4..].chars().chain(code[..4].chars())) == Some(1)
+}
+
+/// Compute the two check digits of the IBAN for `bban` in `country`.
+pub fn check_digits(country: &str, bban: &str) -> Option<u8> {
+    let bban = strip(bban);
+
+    if country_length(country)? != bban.len() + 4 {
+        return None;
+    }
+    mod97(bban.chars().chain(country.chars()).chain("00".chars())).map(|rem| (98 - rem) as u8)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_valid_ibans() {
+        assert!(is_valid("GB82 WEST 1234 5698 7654 32"));
+        assert!(is_valid("DE89370400440532013000"));
+        assert!(is_valid("NO9386011117947"));
+        assert!(is_valid("be71 0961 2345 6769"));
+    }
+
+    #[test]
+    fn test_wrong_check_digits_are_invalid() {
+        assert!(!is_valid("GB83 WEST 1234 5698 7654 32"));
+        assert!(!is_valid("GB82 WEST 1234 5698 7654 33"));
+    }
+
+    #[test]
+    fn test_wrong_length_for_country_is_invalid() {
+        assert!(!is_valid("GB82 WEST 1234 5698 7654 3"));
+        assert!(!is_valid("DE8937040044053201300"));
+    }
+
+    #[test]
+    fn test_unknown_country_is_invalid() {
+        assert!(!is_valid("ZZ82 WEST 1234 5698 7654 32"));
+    }
+
+    #[test]
+    fn test_invalid_characters() {
+        assert!(!is_valid("GB82 WEST 1234 5698 7654 3-"));
+        assert!(!is_valid("GBÄ2 WEST 1234 5698 7654 32"));
+    }
+
+    #[test]
+    fn test_check_digits() {
+        assert_eq!(check_digits("GB", "WEST 1234 5698 7654 32"), Some(82));
+        assert_eq!(check_digits("DE", "370400440532013000"), Some(89));
+        assert_eq!(check_digits("NO", "86011117947"), Some(93));
+        assert_eq!(check_digits("NO", "8601111794"), None);
+    }
+
+    #[test]
+    fn test_lengths_are_sorted() {
+        assert!(LENGTHS.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+}
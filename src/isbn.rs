@@ -0,0 +1,168 @@
+//! ISBN-10 (mod 11, with 'X' standing for 10) and ISBN-13 (GS1 mod 10)
+//! check digits, and conversion between the two. ISBN-13s are EAN-13s in
+//! the 978 and 979 "Bookland" ranges.
+//!
+//! ```
+//! use rust_exercism::isbn;
+//!
+//! assert!(isbn::is_valid("3-598-21508-8"));
+//! assert_eq!(isbn::to_isbn13("0-306-40615-2").as_deref(), Some("9780306406157"));
+//! ```
+
+use crate::gs1;
+
+/// Prefix ISBN-10s gain when converted to ISBN-13.
+const BOOKLAND_PREFIX: &str = "978";
+
+/// Whether `code` starts with one of the EAN prefixes reserved for books.
+fn is_bookland(code: &str) -> bool {
+    code.starts_with("978") || code.starts_with("979")
+}
+
+/// Drop the hyphens and spaces ISBNs are usually printed with.
+fn strip(code: &str) -> String {
+    code.chars().filter(|c| !matches!(c, '-' | ' ')).collect()
+}
+
+/// Sum the first digits of an ISBN-10 weighted 10, 9, 8, ... along with the
+/// number of characters seen. 'X' is only allowed as the tenth character.
+fn checksum10(code: &str) -> Option<(u32, usize)> {
+    code.chars().try_fold((0, 0), |(sum, count), val| {
+        let num = match val {
+            'X' | 'x' if count == 9 => 10,
+            _ => val.to_digit(10)?,
+        };
+        (count < 10).then(|| (sum + num * (10 - count as u32), count + 1))
+    })
+}
+
+/// Check an ISBN-10, e.g. "3-598-21508-8".
+pub fn is_valid_isbn10(code: &str) -> bool {
+    checksum10(&strip(code)).is_some_and(|(sum, count)| count == 10 && sum % 11 == 0)
+}
+
+/// Check an ISBN-13, e.g. "978-0-306-40615-7".
+pub fn is_valid_isbn13(code: &str) -> bool {
+    let code = strip(code);
+
+    is_bookland(&code) && gs1::kind(&code) == Some(gs1::Gtin::Ean13)
+}
+
+/// Check either form of ISBN.
+pub fn is_valid(code: &str) -> bool {
+    is_valid_isbn10(code) || is_valid_isbn13(code)
+}
+
+/// Compute the character completing the first nine digits of an ISBN-10.
+pub fn isbn10_check_digit(code: &str) -> Option<char> {
+    checksum10(&strip(code))
+        .filter(|&(_, count)| count == 9)
+        .map(|(sum, _)| match (11 - sum % 11) % 11 {
+            10 => 'X',
+            num => char::from_digit(num, 10).unwrap(),
+        })
+}
+
+/// Compute the digit completing the first twelve digits of an ISBN-13,
+/// which must start with 978 or 979.
+pub fn isbn13_check_digit(code: &str) -> Option<u8> {
+    let code = strip(code);
+
+    (code.len() == 12 && is_bookland(&code))
+        .then(|| gs1::check_digit(&code))
+        .flatten()
+}
+
+/// Convert an ISBN-10 to its ISBN-13 form, without separators.
+pub fn to_isbn13(isbn10: &str) -> Option<String> {
+    if !is_valid_isbn10(isbn10) {
+        return None;
+    }
+    let stem = format!("{BOOKLAND_PREFIX}{}", &strip(isbn10)[..9]);
+    let check = gs1::check_digit(&stem)?;
+    Some(format!("{stem}{check}"))
+}
+
+/// Convert an ISBN-13 to its ISBN-10 form, without separators. Only
+/// ISBN-13s in the 978 range have one.
+pub fn to_isbn10(isbn13: &str) -> Option<String> {
+    if !is_valid_isbn13(isbn13) {
+        return None;
+    }
+    let code = strip(isbn13);
+    let stem = code.strip_prefix(BOOKLAND_PREFIX)?.get(..9)?;
+    let check = isbn10_check_digit(stem)?;
+    Some(format!("{stem}{check}"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_valid_isbn10() {
+        assert!(is_valid_isbn10("3-598-21508-8"));
+        assert!(is_valid_isbn10("0306406152"));
+    }
+
+    #[test]
+    fn test_valid_isbn10_with_x_check_character() {
+        assert!(is_valid_isbn10("3-598-21507-X"));
+        assert!(is_valid_isbn10("080442957x"));
+    }
+
+    #[test]
+    fn test_invalid_isbn10() {
+        assert!(!is_valid_isbn10("3-598-21508-9"));
+        assert!(!is_valid_isbn10("3-598-2X507-9"));
+        assert!(!is_valid_isbn10("3-598-21507"));
+        assert!(!is_valid_isbn10("3-598-21507-XX"));
+    }
+
+    #[test]
+    fn test_isbn13() {
+        assert!(is_valid_isbn13("978-0-306-40615-7"));
+        assert!(!is_valid_isbn13("978-0-306-40615-8"));
+        // a valid EAN-13 that isn't in the Bookland range
+        assert!(!is_valid_isbn13("4006381333931"));
+    }
+
+    #[test]
+    fn test_is_valid_accepts_either_form() {
+        assert!(is_valid("0306406152"));
+        assert!(is_valid("9780306406157"));
+        assert!(!is_valid("9780306406158"));
+    }
+
+    #[test]
+    fn test_check_digits() {
+        assert_eq!(isbn10_check_digit("3-598-21507"), Some('X'));
+        assert_eq!(isbn10_check_digit("030640615"), Some('2'));
+        assert_eq!(isbn13_check_digit("978-0-306-40615"), Some(7));
+        assert_eq!(isbn10_check_digit("03064061"), None);
+        assert_eq!(isbn13_check_digit("97803064061"), None);
+        // twelve digits of an EAN-13 outside the Bookland range
+        assert_eq!(isbn13_check_digit("400638133393"), None);
+        assert_eq!(isbn13_check_digit("979-10-90636-07"), Some(1));
+    }
+
+    #[test]
+    fn test_isbn10_to_isbn13() {
+        assert_eq!(
+            to_isbn13("0-306-40615-2"),
+            Some("9780306406157".to_string())
+        );
+        assert_eq!(to_isbn13("080442957X"), Some("9780804429573".to_string()));
+        assert_eq!(to_isbn13("0-306-40615-3"), None);
+    }
+
+    #[test]
+    fn test_isbn13_to_isbn10() {
+        assert_eq!(
+            to_isbn10("978-0-306-40615-7"),
+            Some("0306406152".to_string())
+        );
+        assert_eq!(to_isbn10("9780804429573"), Some("080442957X".to_string()));
+        assert_eq!(to_isbn10("9791090636071"), None);
+    }
+}
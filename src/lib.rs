@@ -1,11 +1,14 @@
 #![allow(unused)]
 
-mod anagram;
+pub mod anagram;
 mod clock;
 mod gigasecond;
-mod luhn;
+pub mod gs1;
+pub mod iban;
+pub mod isbn;
+pub mod luhn;
 mod minesweeper;
-mod parallel_letter_frequency;
+pub mod parallel_letter_frequency;
 mod reverse_string;
 mod space_age;
 mod sublist;
@@ -31,24 +31,149 @@
 // 7+2+5+3+2+2+6+2+5+3+1+2+0+5+3+9 = 57
 // 57 is not evenly divisible by 10, so this number is not valid.
 
-/// Check a Luhn checksum.
-pub fn is_valid(code: &str) -> bool {
+use std::fmt::{self, Display, Formatter};
+
+mod bytes;
+mod format;
+mod generator;
+mod identifier;
+mod literal;
+mod options;
+mod state;
+
+pub use bytes::{is_valid_bytes, is_valid_u64, validate_lines};
+pub use format::{format, format_card, mask, normalize, CardBrand, MASK_CHAR};
+pub use generator::{LuhnGenerator, Mistake, NearMiss};
+pub use identifier::{Imei, Isin, Npi, ParseIdentifierError, Sin, SinRegion};
+pub use literal::is_valid_const;
+#[doc(hidden)]
+pub use literal::{digit_count, strip_separators};
+pub use options::{is_valid_with, normalize_with, LuhnOptions, Separators};
+pub use state::LuhnState;
+
+/// Luhn contribution of each digit, indexed by `[doubled][digit]`.
+const TABLE: [[u8; 10]; 2] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+    [0, 2, 4, 6, 8, 1, 3, 5, 7, 9],
+];
+
+/// Sum the Luhn-transformed digits of `code`, doubling every second digit
+/// from the right starting at `offset`, along with the number of digits seen.
+fn checksum(code: &str, offset: usize) -> Option<(u32, usize)> {
     code.chars()
         .rev()
         .filter(|c| !c.is_whitespace())
         .try_fold((0, 0), |(sum, count), val| {
             val.to_digit(10)
-                .map(|num| if count % 2 == 1 { num * 2 } else { num })
+                .map(|num| {
+                    if (count + offset) % 2 == 1 {
+                        num * 2
+                    } else {
+                        num
+                    }
+                })
                 .map(|num| if num > 9 { num - 9 } else { num })
                 .map(|num| (num + sum, count + 1))
         })
-        .map_or(false, |(sum, count)| sum % 10 == 0 && count > 1)
+}
+
+/// Check a Luhn checksum.
+pub fn is_valid(code: &str) -> bool {
+    checksum(code, 0).is_some_and(|(sum, count)| sum % 10 == 0 && count > 1)
+}
+
+/// Compute the digit that, appended to `code`, makes it pass [`is_valid`].
+pub fn check_digit(code: &str) -> Option<u8> {
+    checksum(code, 1)
+        .filter(|&(_, count)| count > 0)
+        .map(|(sum, _)| ((10 - sum % 10) % 10) as u8)
+}
+
+/// Why a number fails the Luhn check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LuhnError {
+    /// Something other than a digit or whitespace, at a character index.
+    InvalidCharacter { position: usize, found: char },
+    /// Fewer than two digits.
+    TooShort,
+    /// The last digit doesn't match the ones before it.
+    WrongCheckDigit { found: u8, expected: u8 },
+}
+
+impl Display for LuhnError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidCharacter { position, found } => {
+                write!(f, "invalid character {found:?} at position {position}")
+            }
+            Self::TooShort => write!(f, "fewer than two digits"),
+            Self::WrongCheckDigit { found, expected } => {
+                write!(f, "check digit is {found}, expected {expected}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LuhnError {}
+
+/// Check a Luhn checksum like [`is_valid`], explaining any failure.
+pub fn validate(code: &str) -> Result<(), LuhnError> {
+    if let Some((position, found)) = code
+        .chars()
+        .enumerate()
+        .find(|(_, c)| !c.is_ascii_digit() && !c.is_whitespace())
+    {
+        return Err(LuhnError::InvalidCharacter { position, found });
+    }
+
+    let mut digits = code.char_indices().filter(|(_, c)| c.is_ascii_digit());
+    let (last, found) = match (digits.next_back(), digits.next()) {
+        (Some(last), Some(_)) => last,
+        _ => return Err(LuhnError::TooShort),
+    };
+    let found = found as u8 - b'0';
+    let expected = check_digit(&code[..last]).unwrap();
+
+    if found == expected {
+        Ok(())
+    } else {
+        Err(LuhnError::WrongCheckDigit { found, expected })
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    /// The exercism cases plus a few whitespace and Unicode ones, for
+    /// checking the other validators against [`is_valid`].
+    pub(super) const CASES: [&str; 24] = [
+        "1",
+        "0",
+        "059",
+        "59",
+        "055 444 285",
+        "055 444 286",
+        "8273 1232 7352 0569",
+        "095 245 88",
+        "055a 444 285",
+        "055-444-285",
+        "055£ 444$ 285",
+        " 0",
+        "0000 0",
+        "091",
+        ":9",
+        "059a",
+        "055# 444$ 285",
+        "055b 444 285",
+        "234 567 891 234",
+        "59%59",
+        "4539\t3195\r\n0343\x0B6467",
+        "4539\u{2003}3195 0343 6467",
+        "4539\u{a0}3195 0343 6466",
+        "",
+    ];
+
     fn process_valid_case(number: &str, is_luhn_expected: bool) {
         assert_eq!(is_valid(number), is_luhn_expected);
     }
@@ -162,4 +287,43 @@ mod test {
     fn test_invalid_char_in_middle_with_sum_divisible_by_10_isnt_allowed() {
         process_valid_case("59%59", false);
     }
+
+    #[test]
+    fn test_check_digit_completes_a_valid_number() {
+        assert_eq!(check_digit("4539 3195 0343 646"), Some(7));
+        assert_eq!(check_digit("055 444 28"), Some(5));
+        assert_eq!(check_digit("05"), Some(9));
+    }
+
+    #[test]
+    fn test_check_digit_of_invalid_input() {
+        assert_eq!(check_digit(""), None);
+        assert_eq!(check_digit("055a"), None);
+    }
+
+    #[test]
+    fn test_validate_agrees_with_is_valid() {
+        for code in CASES {
+            assert_eq!(validate(code).is_ok(), is_valid(code), "{code:?}");
+        }
+    }
+
+    #[test]
+    fn test_validate_explains_failures() {
+        assert_eq!(
+            validate("055-444-285"),
+            Err(LuhnError::InvalidCharacter {
+                position: 3,
+                found: '-'
+            })
+        );
+        assert_eq!(validate(" 0"), Err(LuhnError::TooShort));
+        assert_eq!(
+            validate("8273 1232 7352 0569"),
+            Err(LuhnError::WrongCheckDigit {
+                found: 9,
+                expected: 2
+            })
+        );
+    }
 }
@@ -0,0 +1,122 @@
+//! Allocation-free Luhn validation over bytes and integers, for bulk input.
+
+use super::{is_valid, TABLE};
+
+/// The ASCII characters `char::is_whitespace` accepts.
+pub(super) const fn is_separator(byte: u8) -> bool {
+    matches!(byte, b'\t' | b'\n' | 0x0B | 0x0C | b'\r' | b' ')
+}
+
+/// Check a Luhn checksum over ASCII input with a table lookup per byte, or
+/// return `None` on reaching a non-ASCII byte.
+pub(super) const fn is_valid_ascii(code: &[u8]) -> Option<bool> {
+    let mut sum = 0u64;
+    let mut count = 0usize;
+    let mut i = code.len();
+
+    while i > 0 {
+        i -= 1;
+        match code[i] {
+            byte @ b'0'..=b'9' => {
+                sum += TABLE[count & 1][(byte - b'0') as usize] as u64;
+                count += 1;
+            }
+            byte if is_separator(byte) => {}
+            0x80.. => return None,
+            _ => return Some(false),
+        }
+    }
+    Some(sum % 10 == 0 && count > 1)
+}
+
+/// Check a Luhn checksum over raw bytes, with the same result as
+/// [`is_valid`] on the equivalent string.
+///
+/// ASCII input is handled with a table lookup per byte; anything else falls
+/// back to [`is_valid`] so Unicode whitespace is treated the same way.
+pub fn is_valid_bytes(code: &[u8]) -> bool {
+    is_valid_ascii(code).unwrap_or_else(|| std::str::from_utf8(code).is_ok_and(is_valid))
+}
+
+/// Check a Luhn checksum over the decimal digits of `number`.
+///
+/// Leading zeros can't be represented, which doesn't change the checksum,
+/// but a `number` below 10 has a single digit and is never valid.
+pub fn is_valid_u64(mut number: u64) -> bool {
+    if number < 10 {
+        return false;
+    }
+    let mut sum = 0u32;
+    let mut doubled = 0;
+
+    while number > 0 {
+        sum += TABLE[doubled][(number % 10) as usize] as u32;
+        doubled ^= 1;
+        number /= 10;
+    }
+    sum % 10 == 0
+}
+
+/// Validate every line of a newline-delimited `buffer`, in order.
+///
+/// A trailing newline doesn't start another line, and carriage returns are
+/// whitespace so CRLF files work unchanged.
+pub fn validate_lines(buffer: &[u8]) -> impl Iterator<Item = bool> + '_ {
+    let buffer = buffer.strip_suffix(b"\n").unwrap_or(buffer);
+
+    (!buffer.is_empty())
+        .then(|| buffer.split(|&b| b == b'\n'))
+        .into_iter()
+        .flatten()
+        .map(is_valid_bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::CASES;
+    use super::*;
+
+    #[test]
+    fn test_bytes_match_is_valid() {
+        for case in CASES {
+            assert_eq!(is_valid_bytes(case.as_bytes()), is_valid(case), "{case:?}");
+        }
+    }
+
+    #[test]
+    fn test_invalid_utf8_is_invalid() {
+        assert!(!is_valid_bytes(b"059\xff"));
+    }
+
+    #[test]
+    fn test_u64_matches_is_valid() {
+        for number in (0..100_000)
+            .chain(4539319503436460..4539319503436470)
+            .chain([u64::MAX])
+        {
+            assert_eq!(
+                is_valid_u64(number),
+                is_valid(&number.to_string()),
+                "{number}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_lines() {
+        let buffer = b"4539 3195 0343 6467\r\n8273 1232 7352 0569\n\n059\n";
+        assert_eq!(
+            validate_lines(buffer).collect::<Vec<_>>(),
+            vec![true, false, false, true]
+        );
+    }
+
+    #[test]
+    fn test_validate_lines_without_trailing_newline() {
+        assert_eq!(
+            validate_lines(b"059\n59").collect::<Vec<_>>(),
+            vec![true, true]
+        );
+        assert_eq!(validate_lines(b"").count(), 0);
+    }
+}
@@ -0,0 +1,201 @@
+//! Grouping, masking and lenient normalisation of Luhn-validated numbers.
+
+/// Character used in place of hidden digits by [`mask`].
+pub const MASK_CHAR: char = '•';
+
+/// Card brands recognised from their issuer identification number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardBrand {
+    Visa,
+    Mastercard,
+    Amex,
+    Discover,
+    DinersClub,
+    Jcb,
+}
+
+impl CardBrand {
+    /// Detect the brand of a normalised card number from its leading digits.
+    pub fn detect(number: &str) -> Option<Self> {
+        let prefix = |len: usize| number.get(..len).and_then(|p| p.parse::<u32>().ok());
+
+        match (prefix(1), prefix(2), prefix(3), prefix(4)) {
+            (_, Some(34 | 37), _, _) => Some(Self::Amex),
+            (_, Some(36 | 38 | 39), _, _) | (_, _, Some(300..=305), _) => Some(Self::DinersClub),
+            (_, _, _, Some(3528..=3589)) => Some(Self::Jcb),
+            (Some(4), _, _, _) => Some(Self::Visa),
+            (_, Some(51..=55), _, _) | (_, _, _, Some(2221..=2720)) => Some(Self::Mastercard),
+            (_, Some(65), _, _) | (_, _, Some(644..=649), _) | (_, _, _, Some(6011)) => {
+                Some(Self::Discover)
+            }
+            _ => None,
+        }
+    }
+
+    /// The grouping pattern printed on cards of this brand.
+    pub fn grouping(&self) -> &'static [usize] {
+        match self {
+            Self::Amex => &[4, 6, 5],
+            Self::DinersClub => &[4, 6, 4],
+            _ => &[4],
+        }
+    }
+}
+
+/// Strip spaces and dashes from `code`, returning `None` if anything other
+/// than ASCII digits remains.
+pub fn normalize(code: &str) -> Option<String> {
+    code.chars()
+        .filter(|c| !matches!(c, ' ' | '-'))
+        .map(|c| c.is_ascii_digit().then_some(c))
+        .collect()
+}
+
+/// Split `number` into space-separated groups of the sizes in `pattern`.
+///
+/// Once the pattern is exhausted its last size repeats, so `&[4]` groups by
+/// four and `&[4, 6, 5]` lays out an Amex number.
+pub fn format(number: &str, pattern: &[usize]) -> String {
+    let mut sizes = pattern.iter().copied().filter(|&size| size > 0);
+    let tail = pattern.iter().rev().copied().find(|&size| size > 0);
+    let mut next_size = || sizes.next().or(tail).unwrap_or(usize::MAX);
+    let mut result = String::with_capacity(number.len() * 2);
+    let mut remaining = next_size();
+
+    for c in number.chars() {
+        if remaining == 0 {
+            result.push(' ');
+            remaining = next_size();
+        }
+        result.push(c);
+        remaining -= 1;
+    }
+    result
+}
+
+/// Group a card number according to its detected brand, by four otherwise.
+pub fn format_card(number: &str) -> String {
+    format(
+        number,
+        CardBrand::detect(number).map_or(&[4], |b| b.grouping()),
+    )
+}
+
+/// Replace every ASCII digit of `number` with [`MASK_CHAR`] except for the
+/// first `keep_leading` and the last `keep_trailing` digits. Separators and
+/// anything else are kept, so a formatted number stays formatted.
+pub fn mask(number: &str, keep_leading: usize, keep_trailing: usize) -> String {
+    let len = number.chars().filter(char::is_ascii_digit).count();
+    let mut i = 0;
+
+    number
+        .chars()
+        .map(|c| {
+            if !c.is_ascii_digit() {
+                return c;
+            }
+            i += 1;
+            if i <= keep_leading || i + keep_trailing > len {
+                c
+            } else {
+                MASK_CHAR
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_normalize_strips_spaces_and_dashes() {
+        assert_eq!(
+            normalize("4539 3195-0343 6467"),
+            Some("4539319503436467".to_string())
+        );
+    }
+
+    #[test]
+    fn test_normalize_rejects_other_characters() {
+        assert_eq!(normalize("4539.3195"), None);
+        assert_eq!(normalize("055a 444 285"), None);
+    }
+
+    #[test]
+    fn test_format_by_four() {
+        assert_eq!(format("4539319503436467", &[4]), "4539 3195 0343 6467");
+    }
+
+    #[test]
+    fn test_format_uneven_tail() {
+        assert_eq!(
+            format("4539319503436467123", &[4]),
+            "4539 3195 0343 6467 123"
+        );
+    }
+
+    #[test]
+    fn test_format_amex_pattern() {
+        assert_eq!(format("378282246310005", &[4, 6, 5]), "3782 822463 10005");
+    }
+
+    #[test]
+    fn test_format_empty_pattern_keeps_one_group() {
+        assert_eq!(format("055444285", &[]), "055444285");
+    }
+
+    #[test]
+    fn test_detect_brands() {
+        assert_eq!(CardBrand::detect("4539319503436467"), Some(CardBrand::Visa));
+        assert_eq!(
+            CardBrand::detect("5555555555554444"),
+            Some(CardBrand::Mastercard)
+        );
+        assert_eq!(
+            CardBrand::detect("2221000000000009"),
+            Some(CardBrand::Mastercard)
+        );
+        assert_eq!(CardBrand::detect("378282246310005"), Some(CardBrand::Amex));
+        assert_eq!(
+            CardBrand::detect("6011111111111117"),
+            Some(CardBrand::Discover)
+        );
+        assert_eq!(
+            CardBrand::detect("30569309025904"),
+            Some(CardBrand::DinersClub)
+        );
+        assert_eq!(CardBrand::detect("3530111333300000"), Some(CardBrand::Jcb));
+        assert_eq!(CardBrand::detect("055444285"), None);
+    }
+
+    #[test]
+    fn test_format_card_uses_brand_grouping() {
+        assert_eq!(format_card("378282246310005"), "3782 822463 10005");
+        assert_eq!(format_card("4539319503436467"), "4539 3195 0343 6467");
+    }
+
+    #[test]
+    fn test_mask_keeps_trailing_digits() {
+        assert_eq!(
+            format(&mask("4539319503436467", 0, 4), &[4]),
+            "•••• •••• •••• 6467"
+        );
+    }
+
+    #[test]
+    fn test_mask_keeps_leading_and_trailing_digits() {
+        assert_eq!(mask("4539319503436467", 6, 4), "453931••••••6467");
+    }
+
+    #[test]
+    fn test_mask_keeps_separators() {
+        assert_eq!(mask("4539 3195 0343 6467", 0, 4), "•••• •••• •••• 6467");
+        assert_eq!(mask("4539-3195-0343-6467", 6, 4), "4539-31••-••••-6467");
+    }
+
+    #[test]
+    fn test_mask_short_number_is_unchanged() {
+        assert_eq!(mask("059", 2, 2), "059");
+    }
+}
@@ -0,0 +1,161 @@
+//! Reproducible fixtures of valid and almost-valid Luhn numbers.
+
+use super::check_digit;
+
+/// The single mistake introduced into a near-miss number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mistake {
+    /// The digit at `position` was replaced; it used to be `original`.
+    Substitution { position: usize, original: u8 },
+    /// The digits at `position` and `position + 1` were swapped.
+    Transposition { position: usize },
+}
+
+/// An invalid number one known mistake away from a valid one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NearMiss {
+    pub number: String,
+    pub mistake: Mistake,
+}
+
+/// Seeded generator of Luhn numbers with a fixed prefix and length.
+///
+/// The same seed, prefix and length always produce the same sequence, so
+/// fixtures can be regenerated instead of checked in.
+#[derive(Debug, Clone)]
+pub struct LuhnGenerator {
+    state: u64,
+    prefix: String,
+    len: usize,
+}
+
+impl LuhnGenerator {
+    /// Returns `None` unless `prefix` is all ASCII digits and leaves room for
+    /// at least the check digit within `len`.
+    pub fn new(seed: u64, prefix: &str, len: usize) -> Option<Self> {
+        (prefix.bytes().all(|b| b.is_ascii_digit()) && prefix.len() < len && len > 1).then(|| {
+            Self {
+                state: seed,
+                prefix: prefix.to_string(),
+                len,
+            }
+        })
+    }
+
+    /// SplitMix64, which is enough for fixtures and needs no dependency.
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// The next valid number.
+    pub fn valid(&mut self) -> String {
+        let mut number = self.prefix.clone();
+        while number.len() < self.len - 1 {
+            number.push((b'0' + self.below(10) as u8) as char);
+        }
+        let check = check_digit(&number).unwrap();
+        number.push((b'0' + check) as char);
+        number
+    }
+
+    /// The next invalid number, made from a valid one by substituting a digit
+    /// or swapping two adjacent ones after the prefix.
+    ///
+    /// The Luhn formula catches every such mistake except swapping "09" and
+    /// "90", which is never generated.
+    pub fn near_miss(&mut self) -> NearMiss {
+        let mut digits = self.valid().into_bytes();
+        let start = self.prefix.len();
+        let position = start + self.below(self.len - start);
+
+        let swappable = position + 1 < self.len && {
+            let (a, b) = (digits[position], digits[position + 1]);
+            a != b && a.abs_diff(b) != 9
+        };
+        let mistake = if swappable && self.below(2) == 0 {
+            digits.swap(position, position + 1);
+            Mistake::Transposition { position }
+        } else {
+            let original = digits[position] - b'0';
+            let replacement = (original as usize + 1 + self.below(9)) % 10;
+            digits[position] = b'0' + replacement as u8;
+            Mistake::Substitution { position, original }
+        };
+
+        NearMiss {
+            number: String::from_utf8(digits).unwrap(),
+            mistake,
+        }
+    }
+}
+
+impl Iterator for LuhnGenerator {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.valid())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::is_valid;
+    use super::*;
+
+    #[test]
+    fn test_rejects_bad_configuration() {
+        assert!(LuhnGenerator::new(1, "45a", 16).is_none());
+        assert!(LuhnGenerator::new(1, "4539", 4).is_none());
+        assert!(LuhnGenerator::new(1, "", 1).is_none());
+    }
+
+    #[test]
+    fn test_valid_numbers_have_prefix_and_length() {
+        for number in LuhnGenerator::new(7, "453931", 16).unwrap().take(1000) {
+            assert!(number.starts_with("453931"));
+            assert_eq!(number.len(), 16);
+            assert!(is_valid(&number), "{number}");
+        }
+    }
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let a: Vec<String> = LuhnGenerator::new(42, "35", 15).unwrap().take(20).collect();
+        let b: Vec<String> = LuhnGenerator::new(42, "35", 15).unwrap().take(20).collect();
+        let c: Vec<String> = LuhnGenerator::new(43, "35", 15).unwrap().take(20).collect();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_prefix_can_fill_all_but_check_digit() {
+        let mut generator = LuhnGenerator::new(0, "05544428", 9).unwrap();
+        assert_eq!(generator.valid(), "055444285");
+    }
+
+    #[test]
+    fn test_near_misses_are_invalid_and_undoable() {
+        let mut generator = LuhnGenerator::new(3, "4", 16).unwrap();
+
+        for _ in 0..1000 {
+            let NearMiss { number, mistake } = generator.near_miss();
+            assert!(!is_valid(&number), "{number}");
+            assert!(number.starts_with('4'));
+
+            let mut fixed = number.into_bytes();
+            match mistake {
+                Mistake::Substitution { position, original } => fixed[position] = b'0' + original,
+                Mistake::Transposition { position } => fixed.swap(position, position + 1),
+            }
+            assert!(is_valid(std::str::from_utf8(&fixed).unwrap()));
+        }
+    }
+}
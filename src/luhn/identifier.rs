@@ -0,0 +1,368 @@
+//! Concrete identifiers whose check digit is computed with the Luhn formula.
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use super::{is_valid, normalize};
+
+/// Reasons an identifier can fail to parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseIdentifierError {
+    /// The input contains a character the identifier does not allow.
+    InvalidCharacter,
+    /// The identifier has the wrong number of characters.
+    InvalidLength { expected: usize, found: usize },
+    /// The identifier starts with a prefix that is never issued.
+    InvalidPrefix,
+    /// The ISIN country code is not a known ISO 3166-1 alpha-2 code.
+    UnknownCountry,
+    /// The check digit does not match the rest of the identifier.
+    InvalidCheckDigit,
+}
+
+impl Display for ParseIdentifierError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidCharacter => write!(f, "invalid character"),
+            Self::InvalidLength { expected, found } => {
+                write!(f, "expected {expected} characters, found {found}")
+            }
+            Self::InvalidPrefix => write!(f, "invalid prefix"),
+            Self::UnknownCountry => write!(f, "unknown country code"),
+            Self::InvalidCheckDigit => write!(f, "invalid check digit"),
+        }
+    }
+}
+
+impl std::error::Error for ParseIdentifierError {}
+
+/// Normalise `code` and make sure it holds exactly `len` digits.
+fn parse_digits(code: &str, len: usize) -> Result<String, ParseIdentifierError> {
+    let digits = normalize(code).ok_or(ParseIdentifierError::InvalidCharacter)?;
+
+    if digits.len() != len {
+        return Err(ParseIdentifierError::InvalidLength {
+            expected: len,
+            found: digits.len(),
+        });
+    }
+    Ok(digits)
+}
+
+fn check_luhn(digits: &str) -> Result<(), ParseIdentifierError> {
+    if is_valid(digits) {
+        Ok(())
+    } else {
+        Err(ParseIdentifierError::InvalidCheckDigit)
+    }
+}
+
+/// A 15-digit International Mobile Equipment Identity.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Imei(String);
+
+impl Imei {
+    /// The 8-digit Type Allocation Code identifying the device model.
+    pub fn tac(&self) -> &str {
+        &self.0[..8]
+    }
+
+    /// The 6-digit serial number assigned by the manufacturer.
+    pub fn serial(&self) -> &str {
+        &self.0[8..14]
+    }
+
+    pub fn check_digit(&self) -> u8 {
+        self.0.as_bytes()[14] - b'0'
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Imei {
+    type Err = ParseIdentifierError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = parse_digits(s, 15)?;
+        check_luhn(&digits)?;
+        Ok(Self(digits))
+    }
+}
+
+/// Region of registration encoded in the first digit of a SIN.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SinRegion {
+    Atlantic,
+    Quebec,
+    Ontario,
+    Prairies,
+    Pacific,
+    /// Issued to temporary residents rather than by province.
+    Temporary,
+}
+
+impl SinRegion {
+    /// Postal abbreviations of the provinces and territories in the region.
+    pub fn provinces(&self) -> &'static [&'static str] {
+        match self {
+            Self::Atlantic => &["NB", "NL", "NS", "PE"],
+            Self::Quebec => &["QC"],
+            Self::Ontario => &["ON"],
+            Self::Prairies => &["AB", "MB", "NT", "NU", "SK"],
+            Self::Pacific => &["BC", "YT"],
+            Self::Temporary => &[],
+        }
+    }
+}
+
+/// A 9-digit Canadian Social Insurance Number.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Sin(String);
+
+impl Sin {
+    /// Decode the region of registration, if the first digit names one.
+    pub fn region(&self) -> Option<SinRegion> {
+        match self.0.as_bytes()[0] {
+            b'1' => Some(SinRegion::Atlantic),
+            b'2' | b'3' => Some(SinRegion::Quebec),
+            b'4' | b'5' => Some(SinRegion::Ontario),
+            b'6' => Some(SinRegion::Prairies),
+            b'7' => Some(SinRegion::Pacific),
+            b'9' => Some(SinRegion::Temporary),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Sin {
+    type Err = ParseIdentifierError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = parse_digits(s, 9)?;
+        check_luhn(&digits)?;
+        Ok(Self(digits))
+    }
+}
+
+/// Prefix implicitly prepended to an NPI before its Luhn check.
+const NPI_PREFIX: &str = "80840";
+
+/// A 10-digit US National Provider Identifier.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Npi(String);
+
+impl Npi {
+    pub fn check_digit(&self) -> u8 {
+        self.0.as_bytes()[9] - b'0'
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Npi {
+    type Err = ParseIdentifierError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = parse_digits(s, 10)?;
+        if !digits.starts_with(['1', '2']) {
+            return Err(ParseIdentifierError::InvalidPrefix);
+        }
+        check_luhn(&format!("{NPI_PREFIX}{digits}"))?;
+        Ok(Self(digits))
+    }
+}
+
+/// Country codes an ISIN may start with: ISO 3166-1 alpha-2 plus the
+/// pseudo-countries used by international depositories.
+const ISIN_COUNTRIES: &[&str] = &[
+    "AD", "AE", "AF", "AG", "AI", "AL", "AM", "AO", "AQ", "AR", "AS", "AT", "AU", "AW", "AX", "AZ",
+    "BA", "BB", "BD", "BE", "BF", "BG", "BH", "BI", "BJ", "BL", "BM", "BN", "BO", "BQ", "BR", "BS",
+    "BT", "BV", "BW", "BY", "BZ", "CA", "CC", "CD", "CF", "CG", "CH", "CI", "CK", "CL", "CM", "CN",
+    "CO", "CR", "CU", "CV", "CW", "CX", "CY", "CZ", "DE", "DJ", "DK", "DM", "DO", "DZ", "EC", "EE",
+    "EG", "EH", "ER", "ES", "ET", "EU", "FI", "FJ", "FK", "FM", "FO", "FR", "GA", "GB", "GD", "GE",
+    "GF", "GG", "GH", "GI", "GL", "GM", "GN", "GP", "GQ", "GR", "GS", "GT", "GU", "GW", "GY", "HK",
+    "HM", "HN", "HR", "HT", "HU", "ID", "IE", "IL", "IM", "IN", "IO", "IQ", "IR", "IS", "IT", "JE",
+    "JM", "JO", "JP", "KE", "KG", "KH", "KI", "KM", "KN", "KP", "KR", "KW", "KY", "KZ", "LA", "LB",
+    "LC", "LI", "LK", "LR", "LS", "LT", "LU", "LV", "LY", "MA", "MC", "MD", "ME", "MF", "MG", "MH",
+    "MK", "ML", "MM", "MN", "MO", "MP", "MQ", "MR", "MS", "MT", "MU", "MV", "MW", "MX", "MY", "MZ",
+    "NA", "NC", "NE", "NF", "NG", "NI", "NL", "NO", "NP", "NR", "NU", "NZ", "OM", "PA", "PE", "PF",
+    "PG", "PH", "PK", "PL", "PM", "PN", "PR", "PS", "PT", "PW", "PY", "QA", "RE", "RO", "RS", "RU",
+    "RW", "SA", "SB", "SC", "SD", "SE", "SG", "SH", "SI", "SJ", "SK", "SL", "SM", "SN", "SO", "SR",
+    "SS", "ST", "SV", "SX", "SY", "SZ", "TC", "TD", "TF", "TG", "TH", "TJ", "TK", "TL", "TM", "TN",
+    "TO", "TR", "TT", "TV", "TW", "TZ", "UA", "UG", "UM", "US", "UY", "UZ", "VA", "VC", "VE", "VG",
+    "VI", "VN", "VU", "WF", "WS", "XA", "XB", "XC", "XD", "XS", "YE", "YT", "ZA", "ZM", "ZW",
+];
+
+/// A 12-character International Securities Identification Number.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Isin(String);
+
+impl Isin {
+    /// The ISO 3166-1 alpha-2 code of the issuing country.
+    pub fn country(&self) -> &str {
+        &self.0[..2]
+    }
+
+    /// The 9-character National Securities Identifying Number.
+    pub fn nsin(&self) -> &str {
+        &self.0[2..11]
+    }
+
+    pub fn check_digit(&self) -> u8 {
+        self.0.as_bytes()[11] - b'0'
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Isin {
+    type Err = ParseIdentifierError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.trim();
+        let len = code.chars().count();
+
+        if len != 12 {
+            return Err(ParseIdentifierError::InvalidLength {
+                expected: 12,
+                found: len,
+            });
+        }
+        if !code
+            .bytes()
+            .all(|b| b.is_ascii_digit() || b.is_ascii_uppercase())
+            || !code.as_bytes()[11].is_ascii_digit()
+        {
+            return Err(ParseIdentifierError::InvalidCharacter);
+        }
+        if ISIN_COUNTRIES.binary_search(&&code[..2]).is_err() {
+            return Err(ParseIdentifierError::UnknownCountry);
+        }
+
+        // letters expand to two digits each: A = 10, B = 11, ..., Z = 35
+        let expanded: String = code
+            .chars()
+            .map(|c| c.to_digit(36).unwrap().to_string())
+            .collect();
+        check_luhn(&expanded)?;
+        Ok(Self(code.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_valid_imei() {
+        let imei: Imei = "49-015420-323751-8".parse().unwrap();
+        assert_eq!(imei.as_str(), "490154203237518");
+        assert_eq!(imei.tac(), "49015420");
+        assert_eq!(imei.serial(), "323751");
+        assert_eq!(imei.check_digit(), 8);
+    }
+
+    #[test]
+    fn test_imei_with_bad_check_digit() {
+        assert_eq!(
+            "490154203237519".parse::<Imei>(),
+            Err(ParseIdentifierError::InvalidCheckDigit)
+        );
+    }
+
+    #[test]
+    fn test_imei_wrong_length() {
+        assert_eq!(
+            "4901542032375".parse::<Imei>(),
+            Err(ParseIdentifierError::InvalidLength {
+                expected: 15,
+                found: 13
+            })
+        );
+    }
+
+    #[test]
+    fn test_valid_sin_with_region() {
+        let sin: Sin = "046 454 286".parse().unwrap();
+        assert_eq!(sin.as_str(), "046454286");
+        assert_eq!(sin.region(), None);
+
+        let sin: Sin = "130 692 544".parse().unwrap();
+        assert_eq!(sin.region(), Some(SinRegion::Atlantic));
+        assert!(sin.region().unwrap().provinces().contains(&"NS"));
+    }
+
+    #[test]
+    fn test_invalid_sin() {
+        assert_eq!(
+            "055 444 286".parse::<Sin>(),
+            Err(ParseIdentifierError::InvalidCheckDigit)
+        );
+        assert_eq!(
+            "055a444285".parse::<Sin>(),
+            Err(ParseIdentifierError::InvalidCharacter)
+        );
+    }
+
+    #[test]
+    fn test_valid_npi_uses_implicit_prefix() {
+        let npi: Npi = "1234567893".parse().unwrap();
+        assert_eq!(npi.check_digit(), 3);
+        // without the 80840 prefix the check digit would be wrong
+        assert!(!is_valid("1234567893"));
+    }
+
+    #[test]
+    fn test_invalid_npi() {
+        assert_eq!(
+            "1234567890".parse::<Npi>(),
+            Err(ParseIdentifierError::InvalidCheckDigit)
+        );
+        assert_eq!(
+            "3234567893".parse::<Npi>(),
+            Err(ParseIdentifierError::InvalidPrefix)
+        );
+    }
+
+    #[test]
+    fn test_valid_isin() {
+        let isin: Isin = "US0378331005".parse().unwrap();
+        assert_eq!(isin.country(), "US");
+        assert_eq!(isin.nsin(), "037833100");
+        assert_eq!(isin.check_digit(), 5);
+
+        assert!("AU0000XVGZA3".parse::<Isin>().is_ok());
+        assert!("GB0002634946".parse::<Isin>().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_isin() {
+        assert_eq!(
+            "US0378331006".parse::<Isin>(),
+            Err(ParseIdentifierError::InvalidCheckDigit)
+        );
+        assert_eq!(
+            "QQ0378331005".parse::<Isin>(),
+            Err(ParseIdentifierError::UnknownCountry)
+        );
+        assert_eq!(
+            "us0378331005".parse::<Isin>(),
+            Err(ParseIdentifierError::InvalidCharacter)
+        );
+    }
+
+    #[test]
+    fn test_isin_countries_are_sorted() {
+        assert!(ISIN_COUNTRIES.windows(2).all(|w| w[0] < w[1]));
+    }
+}
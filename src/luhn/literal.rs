@@ -0,0 +1,112 @@
+//! Luhn validation of literals at compile time.
+
+use super::bytes::{is_separator, is_valid_ascii};
+
+/// Check a Luhn checksum in a const context.
+///
+/// Runs the same table-driven loop as
+/// [`is_valid_bytes`](super::is_valid_bytes), but can't decode UTF-8, so
+/// non-ASCII input is always invalid.
+pub const fn is_valid_const(code: &[u8]) -> bool {
+    matches!(is_valid_ascii(code), Some(true))
+}
+
+/// Number of digits left in `code` once separators are dropped.
+#[doc(hidden)]
+pub const fn digit_count(code: &[u8]) -> usize {
+    let mut count = 0;
+    let mut i = 0;
+
+    while i < code.len() {
+        if !is_separator(code[i]) {
+            count += 1;
+        }
+        i += 1;
+    }
+    count
+}
+
+/// The digits of `code` with separators dropped. `N` must be
+/// [`digit_count`] of `code`.
+#[doc(hidden)]
+pub const fn strip_separators<const N: usize>(code: &[u8]) -> [u8; N] {
+    let mut digits = [0; N];
+    let mut count = 0;
+    let mut i = 0;
+
+    while i < code.len() {
+        if !is_separator(code[i]) {
+            digits[count] = code[i];
+            count += 1;
+        }
+        i += 1;
+    }
+    digits
+}
+
+/// Check a Luhn number literal at compile time and expand to it without
+/// separators, as a `&'static str`.
+///
+/// ```
+/// use rust_exercism::luhn;
+///
+/// const CARD: &str = luhn!("4539 3195 0343 6467");
+/// assert_eq!(CARD, "4539319503436467");
+/// ```
+///
+/// A typo stops the build:
+///
+/// ```compile_fail
+/// use rust_exercism::luhn;
+///
+/// const CARD: &str = luhn!("4539 3195 0343 6468");
+/// ```
+#[macro_export]
+macro_rules! luhn {
+    ($code:literal) => {{
+        const CODE: &[u8] = $code.as_bytes();
+        const LEN: usize = $crate::luhn::digit_count(CODE);
+        const DIGITS: [u8; LEN] = $crate::luhn::strip_separators(CODE);
+        const NORMALIZED: &str = {
+            assert!(
+                $crate::luhn::is_valid_const(CODE),
+                concat!("invalid Luhn number: ", $code)
+            );
+            match ::core::str::from_utf8(&DIGITS) {
+                Ok(digits) => digits,
+                Err(_) => unreachable!(),
+            }
+        };
+        NORMALIZED
+    }};
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::is_valid;
+    use super::super::test::CASES;
+    use super::*;
+
+    #[test]
+    fn test_const_matches_is_valid() {
+        for code in CASES.iter().filter(|code| code.is_ascii()) {
+            assert_eq!(is_valid_const(code.as_bytes()), is_valid(code), "{code:?}");
+        }
+    }
+
+    #[test]
+    fn test_const_rejects_non_ascii() {
+        assert!(!is_valid_const("4539\u{2003}3195 0343 6467".as_bytes()));
+    }
+
+    #[test]
+    fn test_const_is_usable_in_const_context() {
+        const { assert!(is_valid_const(b"4539 3195 0343 6467")) };
+    }
+
+    #[test]
+    fn test_macro_strips_separators() {
+        assert_eq!(luhn!("4539 3195 0343 6467"), "4539319503436467");
+        assert_eq!(luhn!("055\t444\t285"), "055444285");
+    }
+}
@@ -0,0 +1,201 @@
+//! Configurable separators and digit sets for Luhn input.
+
+use super::LuhnState;
+
+/// The zero of every run of Unicode decimal digits (general category Nd),
+/// sorted. Each run holds the ten digits 0 to 9 in order.
+const DIGIT_ZEROS: &[u32] = &[
+    0x0030, 0x0660, 0x06F0, 0x07C0, 0x0966, 0x09E6, 0x0A66, 0x0AE6, 0x0B66, 0x0BE6, 0x0C66, 0x0CE6,
+    0x0D66, 0x0DE6, 0x0E50, 0x0ED0, 0x0F20, 0x1040, 0x1090, 0x17E0, 0x1810, 0x1946, 0x19D0, 0x1A80,
+    0x1A90, 0x1B50, 0x1BB0, 0x1C40, 0x1C50, 0xA620, 0xA8D0, 0xA900, 0xA9D0, 0xA9F0, 0xAA50, 0xABF0,
+    0xFF10, 0x104A0, 0x10D30, 0x11066, 0x110F0, 0x11136, 0x111D0, 0x112F0, 0x11450, 0x114D0,
+    0x11650, 0x116C0, 0x11730, 0x118E0, 0x11950, 0x11C50, 0x11D50, 0x11DA0, 0x11F50, 0x16A60,
+    0x16AC0, 0x16B50, 0x1D7CE, 0x1D7D8, 0x1D7E2, 0x1D7EC, 0x1D7F6, 0x1E140, 0x1E2F0, 0x1E4F0,
+    0x1E950, 0x1FBF0,
+];
+
+/// Value of any Unicode decimal digit, e.g. full-width '４' or Arabic-Indic '٤'.
+fn unicode_digit(c: char) -> Option<u8> {
+    let c = c as u32;
+    let zero = match DIGIT_ZEROS.binary_search(&c) {
+        Ok(i) => DIGIT_ZEROS[i],
+        Err(0) => return None,
+        Err(i) => DIGIT_ZEROS[i - 1],
+    };
+    (c - zero < 10).then_some((c - zero) as u8)
+}
+
+/// Characters allowed between digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Separators {
+    /// Any Unicode whitespace, as [`is_valid`](super::is_valid) allows.
+    Whitespace,
+    /// Only the listed characters.
+    Only(&'static [char]),
+}
+
+impl Separators {
+    pub fn contains(&self, c: char) -> bool {
+        match self {
+            Self::Whitespace => c.is_whitespace(),
+            Self::Only(chars) => chars.contains(&c),
+        }
+    }
+}
+
+/// What Luhn input may look like. The default matches
+/// [`is_valid`](super::is_valid).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LuhnOptions {
+    pub separators: Separators,
+    /// Accept decimal digits from any script, not only ASCII.
+    pub unicode_digits: bool,
+    /// Fewest digits a valid number may have. Single digits are never valid.
+    pub min_length: usize,
+}
+
+impl Default for LuhnOptions {
+    fn default() -> Self {
+        Self {
+            separators: Separators::Whitespace,
+            unicode_digits: false,
+            min_length: 2,
+        }
+    }
+}
+
+impl LuhnOptions {
+    fn digit(&self, c: char) -> Option<u8> {
+        if self.unicode_digits {
+            unicode_digit(c)
+        } else {
+            c.to_digit(10).map(|num| num as u8)
+        }
+    }
+}
+
+/// Check a Luhn checksum, reading `code` according to `options`.
+pub fn is_valid_with(code: &str, options: &LuhnOptions) -> bool {
+    let mut state = LuhnState::new();
+
+    code.chars().all(|c| match options.digit(c) {
+        Some(digit) => {
+            state.push(digit);
+            true
+        }
+        None => options.separators.contains(c),
+    }) && state.is_valid()
+        && state.len() >= options.min_length as u64
+}
+
+/// Drop separators and convert digits to ASCII, returning `None` if `code`
+/// contains anything `options` doesn't allow.
+pub fn normalize_with(code: &str, options: &LuhnOptions) -> Option<String> {
+    code.chars()
+        .filter(|&c| !options.separators.contains(c))
+        .map(|c| options.digit(c).map(|num| (b'0' + num) as char))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::is_valid;
+    use super::super::test::CASES;
+    use super::*;
+
+    const SPACES_ONLY: LuhnOptions = LuhnOptions {
+        separators: Separators::Only(&[' ']),
+        unicode_digits: false,
+        min_length: 2,
+    };
+
+    #[test]
+    fn test_default_matches_is_valid() {
+        for code in CASES.iter().chain(&["４５３９ ３１９５ ０３４３ ６４６７"]) {
+            assert_eq!(
+                is_valid_with(code, &LuhnOptions::default()),
+                is_valid(code),
+                "{code:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_explicit_separators() {
+        assert!(is_valid_with("4539 3195 0343 6467", &SPACES_ONLY));
+        assert!(!is_valid_with("4539\u{2003}3195 0343 6467", &SPACES_ONLY));
+
+        let dashes = LuhnOptions {
+            separators: Separators::Only(&[' ', '-']),
+            ..Default::default()
+        };
+        assert!(is_valid_with("055-444-285", &dashes));
+    }
+
+    #[test]
+    fn test_full_width_digits() {
+        let options = LuhnOptions {
+            unicode_digits: true,
+            ..Default::default()
+        };
+        assert!(is_valid_with(
+            "４５３９ ３１９５ ０３４３ ６４６７",
+            &options
+        ));
+        assert!(!is_valid_with(
+            "４５３９ ３１９５ ０３４３ ６４６８",
+            &options
+        ));
+    }
+
+    #[test]
+    fn test_arabic_indic_digits() {
+        let options = LuhnOptions {
+            unicode_digits: true,
+            ..Default::default()
+        };
+        assert!(is_valid_with("٠٥٥ ٤٤٤ ٢٨٥", &options));
+        assert!(is_valid_with("۰۵۹", &options));
+    }
+
+    #[test]
+    fn test_digit_like_characters_are_not_digits() {
+        let options = LuhnOptions {
+            unicode_digits: true,
+            ..Default::default()
+        };
+        // superscript two and circled one are numeric, but not decimal digits
+        assert!(!is_valid_with("05²", &options));
+        assert!(!is_valid_with("0①", &options));
+    }
+
+    #[test]
+    fn test_min_length() {
+        let options = LuhnOptions {
+            min_length: 12,
+            ..Default::default()
+        };
+        assert!(!is_valid_with("055 444 285", &options));
+        assert!(is_valid_with("4539 3195 0343 6467", &options));
+
+        let options = LuhnOptions {
+            min_length: 0,
+            ..Default::default()
+        };
+        assert!(!is_valid_with("0", &options));
+    }
+
+    #[test]
+    fn test_normalize_with() {
+        let options = LuhnOptions {
+            separators: Separators::Only(&[' ', '-', '\u{3000}']),
+            unicode_digits: true,
+            min_length: 2,
+        };
+        assert_eq!(
+            normalize_with("４５３９\u{3000}３１９５-0343 6467", &options),
+            Some("4539319503436467".to_string())
+        );
+        assert_eq!(normalize_with("4539\u{2003}3195", &options), None);
+    }
+}
@@ -0,0 +1,170 @@
+//! Incremental Luhn validation for digits arriving one at a time.
+
+use super::TABLE;
+
+/// Running Luhn checksum, fed left to right.
+///
+/// Since the number of digits still to come is unknown, both checksums are
+/// kept: one as if the last digit pushed were the check digit, and one as if
+/// another digit were still to follow. Both are stored mod 10 so any number
+/// of digits fits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LuhnState {
+    /// Checksum if the number ended here.
+    complete: u8,
+    /// Checksum if one more digit followed.
+    pending: u8,
+    len: u64,
+}
+
+impl LuhnState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a digit to the right of the number.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `digit` is not in `0..=9`.
+    pub fn push(&mut self, digit: u8) {
+        assert!(digit < 10, "not a decimal digit: {digit}");
+
+        // every digit seen so far moves one position further from the end
+        let complete = (self.pending + digit) % 10;
+        let pending = (self.complete + TABLE[1][digit as usize]) % 10;
+        self.complete = complete;
+        self.pending = pending;
+        self.len = self.len.saturating_add(1);
+    }
+
+    /// Append a character the way [`is_valid`](super::is_valid) reads it:
+    /// whitespace is skipped and anything else but a digit is rejected,
+    /// leaving the state unchanged.
+    pub fn push_char(&mut self, c: char) -> bool {
+        match c.to_digit(10) {
+            Some(digit) => self.push(digit as u8),
+            None if c.is_whitespace() => {}
+            None => return false,
+        }
+        true
+    }
+
+    /// Number of digits pushed so far.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the digits pushed so far form a valid Luhn number.
+    pub fn is_valid(&self) -> bool {
+        self.complete == 0 && self.len > 1
+    }
+
+    /// The digit that would make the number valid if pushed next.
+    pub fn check_digit(&self) -> Option<u8> {
+        (!self.is_empty()).then_some((10 - self.pending) % 10)
+    }
+
+    /// Combine the state of a chunk with the state of the chunk following
+    /// it, as if all of `other`'s digits had been pushed onto `self`.
+    pub fn merge(&self, other: &Self) -> Self {
+        let (complete, pending) = if other.len % 2 == 0 {
+            (self.complete, self.pending)
+        } else {
+            (self.pending, self.complete)
+        };
+
+        Self {
+            complete: (complete + other.complete) % 10,
+            pending: (pending + other.pending) % 10,
+            len: self.len.saturating_add(other.len),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::CASES;
+    use super::super::{check_digit, is_valid};
+    use super::*;
+
+    fn state_of(code: &str) -> Option<LuhnState> {
+        let mut state = LuhnState::new();
+        code.chars().all(|c| state.push_char(c)).then_some(state)
+    }
+
+    #[test]
+    fn test_matches_is_valid() {
+        for code in CASES {
+            assert_eq!(
+                state_of(code).is_some_and(|s| s.is_valid()),
+                is_valid(code),
+                "{code:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_valid_at_each_prefix() {
+        let mut state = LuhnState::new();
+        let valid: Vec<bool> = [0, 5, 9]
+            .into_iter()
+            .map(|digit| {
+                state.push(digit);
+                state.is_valid()
+            })
+            .collect();
+        assert_eq!(valid, vec![false, false, true]);
+    }
+
+    #[test]
+    fn test_check_digit_matches() {
+        for code in ["4539 3195 0343 646", "055 444 28", "05", "8"] {
+            assert_eq!(state_of(code).unwrap().check_digit(), check_digit(code));
+        }
+        assert_eq!(LuhnState::new().check_digit(), None);
+    }
+
+    #[test]
+    fn test_rejected_char_leaves_state_unchanged() {
+        let mut state = state_of("059").unwrap();
+        assert!(!state.push_char('-'));
+        assert!(state.is_valid());
+        assert_eq!(state.len(), 3);
+    }
+
+    #[test]
+    fn test_merge_chunks() {
+        let code = "45393195034364674539319503436467";
+        let whole = state_of(code).unwrap();
+
+        for size in 1..code.len() {
+            let merged = code
+                .as_bytes()
+                .chunks(size)
+                .map(|chunk| state_of(std::str::from_utf8(chunk).unwrap()).unwrap())
+                .fold(LuhnState::new(), |acc, state| acc.merge(&state));
+            assert_eq!(merged, whole, "chunk size {size}");
+        }
+    }
+
+    #[test]
+    fn test_long_input_does_not_overflow() {
+        let mut state = LuhnState::new();
+        for _ in 0..1_000_000 {
+            state.push(9);
+        }
+        assert_eq!(state.len(), 1_000_000);
+        assert!(state.check_digit().is_some());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_push_rejects_non_digits() {
+        LuhnState::new().push(10);
+    }
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::len_zero, clippy::char_lit_as_u8)]
+
 // Instructions
 // Add the mine counts to a completed Minesweeper board.
 
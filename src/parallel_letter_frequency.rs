@@ -4,43 +4,222 @@
 // Parallelism is about doing things in parallel that can also be done sequentially. A common example is counting the frequency of letters. Create a function that returns the total frequency of each letter in a list of texts and that employs parallelism.
 
 use std::collections::HashMap;
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+use std::panic::{self, AssertUnwindSafe};
 use std::thread;
 
-pub fn frequency(input: &[&str], worker_count: usize) -> HashMap<char, usize> {
-    let counter = |input: &[&str]| {
-        let mut map = HashMap::new();
-        for line in input {
-            for c in line
-                .chars()
-                .filter(|c| c.is_alphabetic())
-                .map(|c| c.to_ascii_lowercase())
-            {
-                *map.entry(c).or_default() += 1;
+use unicode_segmentation::GraphemeCursor;
+
+mod counter;
+mod options;
+mod workers;
+
+pub use counter::FrequencyCounter;
+pub use options::{frequency_with, try_frequency_with, Case, FrequencyOptions};
+pub use workers::{FrequencyError, Workers};
+
+/// Each extra thread has to have at least this many bytes to count to be
+/// worth starting.
+const MIN_BYTES_PER_WORKER: usize = 16 * 1024;
+
+/// Add the letters of `text` to `map`, lowercased. A letter whose lowercase
+/// form is several characters, like "İ", counts each letter in it.
+///
+/// ASCII letters are counted byte by byte into an array and only added to
+/// the map at the end; the map is used per character only for the runs of
+/// non-ASCII text in between.
+fn count_into(map: &mut HashMap<char, usize>, text: &str) {
+    let bytes = text.as_bytes();
+    let mut ascii = [0usize; 26];
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i].is_ascii() {
+            // setting 0x20 lowercases letters and moves nothing else into a..=z
+            let letter = (bytes[i] | 0x20).wrapping_sub(b'a');
+            if letter < 26 {
+                ascii[letter as usize] += 1;
             }
+            i += 1;
+            continue;
         }
-        map
-    };
-
-    // redirect to the best implementation.
-    match input.len() {
-        0 => HashMap::new(),
-        n if n < 500 => counter(input),
-        _ => thread::scope(|s| {
-            let mut handles = Vec::with_capacity(worker_count);
-            for lines in input.chunks(input.len() / worker_count + 1) {
-                handles.push(s.spawn(|| counter(lines)))
+        // ASCII bytes never occur inside a multi-byte character, so the run
+        // ends on a char boundary
+        let end = bytes[i..]
+            .iter()
+            .position(u8::is_ascii)
+            .map_or(bytes.len(), |n| i + n);
+        for c in text[i..end].chars().filter(|c| c.is_alphabetic()) {
+            for lower in c.to_lowercase().filter(|c| c.is_alphabetic()) {
+                *map.entry(lower).or_default() += 1;
             }
+        }
+        i = end;
+    }
+
+    for (letter, &count) in (b'a'..=b'z').zip(&ascii) {
+        if count > 0 {
+            *map.entry(letter as char).or_default() += count;
+        }
+    }
+}
+
+/// Add the counts of `from` to `into`.
+fn merge<K: Eq + Hash + Clone>(into: &mut HashMap<K, usize>, from: &HashMap<K, usize>) {
+    for (key, &count) in from {
+        *into.entry(key.clone()).or_default() += count;
+    }
+}
+
+/// How many threads to count `bytes` of text on: no more than asked for or
+/// than the machine runs at once, and only as many as have enough to do.
+fn workers_for(bytes: usize, workers: Workers) -> usize {
+    let cores = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    workers
+        .get()
+        .min(cores)
+        .min(bytes / MIN_BYTES_PER_WORKER)
+        .max(1)
+}
 
-            let mut map = handles.pop().unwrap().join().unwrap();
-            for res in handles {
-                res.join().unwrap().into_iter().for_each(|(k, v)| {
-                    *map.entry(k).or_default() += v;
-                })
+/// The first char boundary of `text` at or after `at`.
+fn char_boundary(text: &str, at: usize) -> usize {
+    (at..text.len())
+        .find(|&i| text.is_char_boundary(i))
+        .unwrap_or(text.len())
+}
+
+/// The first grapheme boundary of `text` at or after `at`.
+fn grapheme_boundary(text: &str, at: usize) -> usize {
+    let at = char_boundary(text, at);
+    let mut cursor = GraphemeCursor::new(at, text.len(), true);
+    match cursor.is_boundary(text, 0) {
+        Ok(true) => at,
+        _ => cursor
+            .next_boundary(text, 0)
+            .ok()
+            .flatten()
+            .unwrap_or(text.len()),
+    }
+}
+
+/// Split `input` into at most `parts` shares of about the same number of
+/// bytes, cutting lines too long for one share where `boundary` allows.
+fn partition<'a>(
+    input: &[&'a str],
+    parts: usize,
+    boundary: fn(&str, usize) -> usize,
+) -> Vec<Vec<&'a str>> {
+    let total: usize = input.iter().map(|line| line.len()).sum();
+    let share = total.div_ceil(parts.max(1)).max(1);
+    let mut shares = vec![Vec::new()];
+    let mut filled = 0;
+
+    for &line in input {
+        let mut rest = line;
+        loop {
+            let room = share.saturating_sub(filled);
+            if rest.len() <= room || shares.len() == parts {
+                filled += rest.len();
+                shares.last_mut().unwrap().push(rest);
+                break;
             }
+            let (head, tail) = rest.split_at(boundary(rest, room));
+            shares.last_mut().unwrap().push(head);
+            shares.push(Vec::new());
+            filled = 0;
+            rest = tail;
+            if rest.is_empty() {
+                break;
+            }
+        }
+    }
+    shares.retain(|share| !share.is_empty());
+    shares
+}
+
+/// Count every line of `input` into one map with `count_line`, spread over
+/// up to `workers` threads by bytes when there are enough of them. Lines
+/// may be cut, where `boundary` allows, to balance the threads.
+///
+/// A panic while counting, on a worker or on the calling thread, is
+/// returned as an error.
+fn count_lines<K, F>(
+    input: &[&str],
+    workers: Workers,
+    boundary: fn(&str, usize) -> usize,
+    count_line: F,
+) -> Result<HashMap<K, usize>, FrequencyError>
+where
+    K: Eq + Hash + Clone + Send,
+    F: Fn(&mut HashMap<K, usize>, &str) + Sync,
+{
+    let bytes = input.iter().map(|line| line.len()).sum();
+    // redirect to the best implementation.
+    match workers_for(bytes, workers) {
+        1 => panic::catch_unwind(AssertUnwindSafe(|| count_share(input, &count_line)))
+            .map_err(FrequencyError::from_panic),
+        workers => count_shares(&partition(input, workers, boundary), &count_line),
+    }
+}
 
-            map
-        }),
+/// Count every line of `lines` into one map with `count_line`.
+fn count_share<K, F>(lines: &[&str], count_line: &F) -> HashMap<K, usize>
+where
+    F: Fn(&mut HashMap<K, usize>, &str),
+{
+    let mut map = HashMap::new();
+    for line in lines {
+        count_line(&mut map, line);
     }
+    map
+}
+
+/// Count each of `shares` on a thread of its own and merge the counts.
+fn count_shares<K, F>(
+    shares: &[Vec<&str>],
+    count_line: &F,
+) -> Result<HashMap<K, usize>, FrequencyError>
+where
+    K: Eq + Hash + Clone + Send,
+    F: Fn(&mut HashMap<K, usize>, &str) + Sync,
+{
+    thread::scope(|s| {
+        let mut handles = Vec::with_capacity(shares.len());
+        for lines in shares {
+            handles.push(s.spawn(|| count_share(lines, count_line)))
+        }
+
+        // join every worker before giving up on one
+        let mut map = HashMap::new();
+        let mut failure = None;
+        for res in handles {
+            match res.join() {
+                Ok(counts) => merge(&mut map, &counts),
+                Err(payload) => failure = Some(FrequencyError::from_panic(payload)),
+            }
+        }
+
+        failure.map_or(Ok(map), Err)
+    })
+}
+
+/// The frequency of each letter in `input`, counted on up to `worker_count`
+/// threads: a number, where 0 means one per core, or a
+/// `Option<NonZeroUsize>`, where `None` does.
+///
+/// Panics if a worker does; see [`try_frequency`].
+pub fn frequency(input: &[&str], worker_count: impl Into<Workers>) -> HashMap<char, usize> {
+    try_frequency(input, worker_count).unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Like [`frequency`], returning a worker's panic as an error.
+pub fn try_frequency(
+    input: &[&str],
+    worker_count: impl Into<Workers>,
+) -> Result<HashMap<char, usize>, FrequencyError> {
+    count_lines(input, worker_count.into(), char_boundary, count_into)
 }
 
 #[cfg(test)]
@@ -157,6 +336,177 @@ mod test {
         assert_eq!(freqs.get(&'ü'), Some(&2));
     }
 
+    #[test]
+    fn test_non_ascii_case_insensitivity() {
+        let mut hm = HashMap::new();
+        hm.insert('ü', 2);
+        hm.insert('σ', 2);
+        hm.insert('ς', 1);
+        assert_eq!(frequency(&["Üü", "Σσς"], 4), hm);
+    }
+
+    #[test]
+    fn test_multi_char_lowercase() {
+        // "İ" lowercases to "i" and a combining dot, which is not a letter
+        let mut hm = HashMap::new();
+        hm.insert('i', 2);
+        assert_eq!(frequency(&["İi"], 4), hm);
+    }
+
+    #[test]
+    fn test_all_three_anthems_uppercase_1_worker() {
+        let mut v = Vec::new();
+        for anthem in [ODE_AN_DIE_FREUDE, WILHELMUS, STAR_SPANGLED_BANNER].iter() {
+            for line in anthem.iter() {
+                v.push(line.to_uppercase());
+            }
+        }
+        let v: Vec<&str> = v.iter().map(String::as_str).collect();
+        let freqs = frequency(&v[..], 1);
+        assert_eq!(freqs.get(&'a'), Some(&49));
+        assert_eq!(freqs.get(&'t'), Some(&56));
+        assert_eq!(freqs.get(&'ü'), Some(&2));
+        assert_eq!(freqs.get(&'ö'), Some(&2));
+        assert_eq!(freqs.get(&'ë'), Some(&1));
+        assert_eq!(freqs.get(&'Ü'), None);
+    }
+
+    #[test]
+    fn test_all_three_anthems_uppercase_3_workers() {
+        let mut v = Vec::new();
+        for anthem in [ODE_AN_DIE_FREUDE, WILHELMUS, STAR_SPANGLED_BANNER].iter() {
+            for line in anthem.iter() {
+                v.push(line.to_uppercase());
+            }
+        }
+        let v: Vec<&str> = v.iter().map(String::as_str).collect();
+        let freqs = frequency(&v[..], 3);
+        assert_eq!(freqs.get(&'a'), Some(&49));
+        assert_eq!(freqs.get(&'t'), Some(&56));
+        assert_eq!(freqs.get(&'ü'), Some(&2));
+        assert_eq!(freqs.get(&'ö'), Some(&2));
+        assert_eq!(freqs.get(&'ë'), Some(&1));
+        assert_eq!(freqs.get(&'Ü'), None);
+    }
+
+    #[test]
+    fn test_partition_balances_bytes() {
+        let input = ["a".repeat(100), "b".repeat(10), "c".repeat(10)];
+        let input: Vec<&str> = input.iter().map(String::as_str).collect();
+        let shares = partition(&input, 4, char_boundary);
+
+        assert_eq!(shares.len(), 4);
+        let sizes: Vec<usize> = shares
+            .iter()
+            .map(|share| share.iter().map(|line| line.len()).sum())
+            .collect();
+        assert_eq!(sizes, vec![30, 30, 30, 30]);
+        assert_eq!(shares.concat().concat(), input.concat());
+    }
+
+    #[test]
+    fn test_partition_cuts_at_char_boundaries() {
+        let input = ["ääääää"];
+        for parts in 1..8 {
+            let shares = partition(&input, parts, char_boundary);
+            assert!(shares.len() <= parts);
+            assert_eq!(shares.concat().concat(), input[0]);
+        }
+        assert_eq!(
+            partition(&input, 4, char_boundary),
+            vec![vec!["ää"], vec!["ää"], vec!["ää"]]
+        );
+    }
+
+    #[test]
+    fn test_partition_keeps_graphemes_whole() {
+        let input = ["e\u{301}e\u{301}e\u{301}"];
+        assert_eq!(
+            partition(&input, 9, grapheme_boundary).concat(),
+            vec!["e\u{301}"; 3]
+        );
+        assert!(partition(&input, 9, char_boundary).concat().contains(&"e"));
+    }
+
+    #[test]
+    fn test_many_tiny_lines_and_one_huge_line() {
+        let huge = "ab".repeat(MIN_BYTES_PER_WORKER * 4);
+        let mut v = vec!["c"; 10_000];
+        v.push(&huge);
+        let mut hm = HashMap::new();
+        hm.insert('a', MIN_BYTES_PER_WORKER * 4);
+        hm.insert('b', MIN_BYTES_PER_WORKER * 4);
+        hm.insert('c', 10_000);
+        assert_eq!(frequency(&v[..], 4), hm);
+    }
+
+    #[test]
+    fn test_zero_workers_means_auto() {
+        let v = vec!["abc"; 1000];
+        let mut hm = HashMap::new();
+        hm.insert('a', 1000);
+        hm.insert('b', 1000);
+        hm.insert('c', 1000);
+        assert_eq!(frequency(&v[..], 0), hm);
+        assert_eq!(frequency(&v[..], None), hm);
+        assert_eq!(frequency(&v[..], NonZeroUsize::new(2)), hm);
+    }
+
+    #[test]
+    fn test_try_frequency() {
+        let v = vec!["abc"; 1000];
+        assert_eq!(try_frequency(&v[..], 4), Ok(frequency(&v[..], 4)));
+    }
+
+    #[test]
+    fn test_worker_panic_is_an_error() {
+        let count_or_panic = |map: &mut HashMap<char, usize>, line: &str| {
+            if line == "boom" {
+                panic!("cannot count {line}");
+            }
+            count_into(map, line)
+        };
+        let expected = Err(FrequencyError::WorkerPanicked(
+            "cannot count boom".to_string(),
+        ));
+
+        let huge = "a".repeat(MIN_BYTES_PER_WORKER * 4);
+        let input = ["boom", &huge];
+        let result = count_lines(&input, Workers::AUTO, char_boundary, count_or_panic);
+        assert_eq!(result, expected);
+
+        // on scoped threads whatever the number of cores, one line a share
+        let input = ["abcd", "boom", "efgh", "ijkl"];
+        let shares = partition(&input, 4, char_boundary);
+        assert_eq!(shares.len(), 4);
+        assert_eq!(count_shares(&shares, &count_or_panic), expected);
+
+        let input = ["abcd", "wxyz", "efgh", "ijkl"];
+        let shares = partition(&input, 4, char_boundary);
+        assert_eq!(
+            count_shares(&shares, &count_or_panic),
+            Ok(frequency(&input, 1))
+        );
+    }
+
+    #[test]
+    fn test_ascii_fast_path_matches_chars() {
+        let text = "@[`{ Az KELVIN \u{212a} İi Ωω ß 123 ÄaA\u{308}";
+        let mut expected = HashMap::new();
+        for c in text.chars().filter(|c| c.is_alphabetic()) {
+            for lower in c.to_lowercase().filter(|c| c.is_alphabetic()) {
+                *expected.entry(lower).or_default() += 1;
+            }
+        }
+        let mut map = HashMap::new();
+        count_into(&mut map, text);
+
+        assert_eq!(map, expected);
+        assert_eq!(map.get(&'k'), Some(&2));
+        assert_eq!(map.get(&'a'), Some(&3));
+        assert_eq!(map.get(&'@'), None);
+    }
+
     #[test]
     fn test_non_integer_multiple_of_threads() {
         let v = vec!["abc"; 999];
@@ -0,0 +1,250 @@
+//! Letter counting with a choice of case mapping, grapheme-level counting
+//! and diacritic stripping.
+
+use std::collections::HashMap;
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{char_boundary, count_lines, grapheme_boundary, FrequencyError, Workers};
+
+/// How letters of different case are merged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Case {
+    /// Lowercase each character on its own, as [`frequency`](super::frequency)
+    /// does, so "Σ", "σ" and "ς" give "σ", "σ" and "ς".
+    #[default]
+    Lower,
+    /// Full case folding, so "ß" counts as two "s" and "ς" as "σ".
+    Fold,
+}
+
+/// Options for [`frequency_with`]. The default counts the same letters as
+/// [`frequency`](super::frequency).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrequencyOptions {
+    pub case: Case,
+    /// Count user-perceived characters, so "e" followed by U+0301 is one
+    /// letter rather than an "e" and a mark that isn't counted.
+    pub graphemes: bool,
+    /// Drop combining marks after case mapping, so "é" counts as "e".
+    pub strip_diacritics: bool,
+}
+
+impl FrequencyOptions {
+    fn count_into(&self, map: &mut HashMap<String, usize>, text: &str) {
+        let mapped: String = match self.case {
+            Case::Lower => text.chars().flat_map(char::to_lowercase).collect(),
+            Case::Fold => text
+                .chars()
+                .flat_map(char::to_uppercase)
+                .flat_map(char::to_lowercase)
+                .collect(),
+        };
+        let text: String = if self.strip_diacritics {
+            mapped
+                .nfd()
+                .filter(|&c| !is_combining_mark(c))
+                .nfc()
+                .collect()
+        } else {
+            mapped
+        };
+
+        let is_letter = |unit: &str| unit.chars().next().is_some_and(char::is_alphabetic);
+        if self.graphemes {
+            for grapheme in text.graphemes(true).filter(|g| is_letter(g)) {
+                add(map, grapheme);
+            }
+        } else {
+            let mut buffer = [0; 4];
+            for c in text.chars().filter(|c| c.is_alphabetic()) {
+                add(map, c.encode_utf8(&mut buffer));
+            }
+        }
+    }
+}
+
+/// Count `letter`, only allocating the first time it's seen.
+fn add(map: &mut HashMap<String, usize>, letter: &str) {
+    match map.get_mut(letter) {
+        Some(count) => *count += 1,
+        None => {
+            map.insert(letter.to_string(), 1);
+        }
+    }
+}
+
+/// The frequency of each letter in `input`, counted as `options` describes
+/// on up to `worker_count` threads, as for [`frequency`](super::frequency).
+/// Letters are strings since a grapheme can be several characters.
+///
+/// Panics if a worker does; see [`try_frequency_with`].
+pub fn frequency_with(
+    input: &[&str],
+    worker_count: impl Into<Workers>,
+    options: &FrequencyOptions,
+) -> HashMap<String, usize> {
+    try_frequency_with(input, worker_count, options).unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Like [`frequency_with`], returning a worker's panic as an error.
+pub fn try_frequency_with(
+    input: &[&str],
+    worker_count: impl Into<Workers>,
+    options: &FrequencyOptions,
+) -> Result<HashMap<String, usize>, FrequencyError> {
+    let boundary = if options.graphemes {
+        grapheme_boundary
+    } else {
+        char_boundary
+    };
+    count_lines(input, worker_count.into(), boundary, |map, line| {
+        options.count_into(map, line)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{frequency, partition};
+    use super::*;
+
+    fn counts(pairs: &[(&str, usize)]) -> HashMap<String, usize> {
+        pairs.iter().map(|&(k, v)| (k.to_string(), v)).collect()
+    }
+
+    #[test]
+    fn test_default_matches_frequency() {
+        let input = ["Grüße aus KÖLN", "ΣΟΦΊΑ σοφία", "İstanbul", "e\u{301}"];
+        let expected: HashMap<String, usize> = frequency(&input, 2)
+            .into_iter()
+            .map(|(c, n)| (c.to_string(), n))
+            .collect();
+        assert_eq!(
+            frequency_with(&input, 2, &FrequencyOptions::default()),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_case_folding() {
+        let options = FrequencyOptions {
+            case: Case::Fold,
+            ..Default::default()
+        };
+        assert_eq!(
+            frequency_with(&["Straße", "ΣΑΣ ςσ"], 1, &options),
+            counts(&[
+                ("s", 3),
+                ("t", 1),
+                ("r", 1),
+                ("a", 1),
+                ("e", 1),
+                ("σ", 4),
+                ("α", 1)
+            ])
+        );
+        assert_eq!(
+            frequency_with(&["ß"], 1, &FrequencyOptions::default()),
+            counts(&[("ß", 1)])
+        );
+    }
+
+    #[test]
+    fn test_graphemes() {
+        let options = FrequencyOptions {
+            graphemes: true,
+            ..Default::default()
+        };
+        let input = ["E\u{301}te\u{301}", "\u{e9}"];
+        assert_eq!(
+            frequency_with(&input, 1, &options),
+            counts(&[("e\u{301}", 2), ("t", 1), ("\u{e9}", 1)])
+        );
+        assert_eq!(
+            frequency_with(&input, 1, &FrequencyOptions::default()),
+            counts(&[("e", 2), ("t", 1), ("\u{e9}", 1)])
+        );
+    }
+
+    #[test]
+    fn test_strip_diacritics() {
+        let options = FrequencyOptions {
+            strip_diacritics: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            frequency_with(&["Éte\u{301}", "Ö ø"], 1, &options),
+            counts(&[("e", 2), ("t", 1), ("o", 1), ("ø", 1)])
+        );
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let options = FrequencyOptions {
+            case: Case::Fold,
+            graphemes: true,
+            strip_diacritics: true,
+        };
+        let input = vec!["Straße naïve Σ"; 1200];
+        assert_eq!(
+            frequency_with(&input, 4, &options),
+            frequency_with(&input, 1, &options)
+        );
+        assert_eq!(frequency_with(&input, 4, &options)["s"], 3600);
+    }
+
+    #[test]
+    fn test_partition_keeps_graphemes_whole() {
+        let options = FrequencyOptions {
+            graphemes: true,
+            ..Default::default()
+        };
+        let lines: Vec<String> = (0..6)
+            .map(|i| {
+                format!(
+                    "{}e\u{301}\u{1f469}\u{200d}\u{1f467}o\u{308}",
+                    "a".repeat(i)
+                )
+            })
+            .collect();
+        let input: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let graphemes = |pieces: &[&str]| -> Vec<String> {
+            pieces
+                .iter()
+                .flat_map(|piece| piece.graphemes(true))
+                .map(str::to_string)
+                .collect()
+        };
+        let whole = graphemes(&input);
+
+        let mut expected = HashMap::new();
+        for line in &input {
+            options.count_into(&mut expected, line);
+        }
+
+        let mut straddled = false;
+        for parts in 2..=input.concat().len() {
+            let by_char: Vec<&str> = partition(&input, parts, char_boundary).concat();
+            straddled |= graphemes(&by_char) != whole;
+
+            let shares = partition(&input, parts, grapheme_boundary);
+            assert_eq!(graphemes(&shares.concat()), whole, "{parts}");
+
+            let mut counts = HashMap::new();
+            for share in &shares {
+                let mut map = HashMap::new();
+                for piece in share {
+                    options.count_into(&mut map, piece);
+                }
+                for (cluster, n) in map {
+                    *counts.entry(cluster).or_default() += n;
+                }
+            }
+            assert_eq!(counts, expected, "{parts}");
+        }
+        // the inputs do put a split inside a cluster
+        assert!(straddled);
+    }
+}
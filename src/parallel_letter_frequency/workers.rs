@@ -0,0 +1,109 @@
+//! How many threads to count on, and what happens when one of them fails.
+
+use std::any::Any;
+use std::fmt::{self, Display, Formatter};
+use std::num::NonZeroUsize;
+use std::thread;
+
+/// The number of worker threads to count on, or `AUTO` for one per core.
+///
+/// Converts from a `usize`, where 0 means `AUTO`, from a [`NonZeroUsize`]
+/// and from an `Option<NonZeroUsize>`, where `None` means `AUTO`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Workers(Option<NonZeroUsize>);
+
+impl Workers {
+    /// As many workers as [`thread::available_parallelism`] suggests.
+    pub const AUTO: Self = Self(None);
+
+    /// The number of threads this stands for, resolving `AUTO`.
+    pub fn get(self) -> usize {
+        self.0
+            .or_else(|| thread::available_parallelism().ok())
+            .map_or(1, NonZeroUsize::get)
+    }
+}
+
+impl From<usize> for Workers {
+    fn from(count: usize) -> Self {
+        Self(NonZeroUsize::new(count))
+    }
+}
+
+impl From<NonZeroUsize> for Workers {
+    fn from(count: NonZeroUsize) -> Self {
+        Self(Some(count))
+    }
+}
+
+impl From<Option<NonZeroUsize>> for Workers {
+    fn from(count: Option<NonZeroUsize>) -> Self {
+        Self(count)
+    }
+}
+
+/// Why counting failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrequencyError {
+    /// A worker panicked, with the panic message when it was a string.
+    WorkerPanicked(String),
+}
+
+impl FrequencyError {
+    pub(super) fn from_panic(payload: Box<dyn Any + Send>) -> Self {
+        let message = match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => payload
+                .downcast_ref::<&str>()
+                .map_or_else(|| "unknown panic".to_string(), |s| s.to_string()),
+        };
+        Self::WorkerPanicked(message)
+    }
+}
+
+impl Display for FrequencyError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::WorkerPanicked(message) => write!(f, "frequency worker panicked: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for FrequencyError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        let four = NonZeroUsize::new(4).unwrap();
+        assert_eq!(Workers::from(4), Workers::from(four));
+        assert_eq!(Workers::from(Some(four)).get(), 4);
+        assert_eq!(Workers::from(0), Workers::AUTO);
+        assert_eq!(Workers::from(None), Workers::AUTO);
+    }
+
+    #[test]
+    fn test_auto_uses_available_parallelism() {
+        let cores = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        assert_eq!(Workers::AUTO.get(), cores);
+        assert_eq!(Workers::default().get(), cores);
+    }
+
+    #[test]
+    fn test_panic_messages() {
+        assert_eq!(
+            FrequencyError::from_panic(Box::new("static")),
+            FrequencyError::WorkerPanicked("static".to_string())
+        );
+        assert_eq!(
+            FrequencyError::from_panic(Box::new(format!("formatted {}", 1))).to_string(),
+            "frequency worker panicked: formatted 1"
+        );
+        assert_eq!(
+            FrequencyError::from_panic(Box::new(7)),
+            FrequencyError::WorkerPanicked("unknown panic".to_string())
+        );
+    }
+}
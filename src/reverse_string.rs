@@ -1,3 +1,5 @@
+#![allow(clippy::empty_line_after_doc_comments)]
+
 use unicode_segmentation::UnicodeSegmentation;
 
 pub fn reverse(input: &str) -> String {
@@ -1,3 +1,5 @@
+#![allow(clippy::needless_borrow, clippy::manual_repeat_n)]
+
 // Instructions
 //
 // Given two lists determine if the first list is contained within the second list, if the second list is contained within the first list, if both lists are contained within each other or if none of these are true.